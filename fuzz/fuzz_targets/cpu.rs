@@ -0,0 +1,10 @@
+#![no_main]
+
+use aoc_rs_2022::solutions::day10::Instr;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(s) = std::str::from_utf8(data) {
+        let _ = s.parse::<Instr>();
+    }
+});
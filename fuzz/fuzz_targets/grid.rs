@@ -0,0 +1,18 @@
+#![no_main]
+
+use aoc_rs_2022::grid::Grid;
+use aoc_rs_2022::solutions::day12::Mark;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(grid) = Grid::<u8>::from_reader(data) else {
+        return;
+    };
+    let _ = grid.to_string();
+    for c in [(0, 0), (grid.w, grid.h), (grid.w.wrapping_sub(1), 0)] {
+        let _ = grid.neighbours8(c).count();
+    }
+    if let Ok(grid) = Grid::<Mark>::from_reader(data) {
+        let _ = grid.to_string();
+    }
+});
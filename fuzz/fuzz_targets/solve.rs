@@ -0,0 +1,24 @@
+#![no_main]
+
+use aoc_rs_2022::solutions::solver;
+use aoc_rs_2022::solver::Parts;
+use libfuzzer_sys::fuzz_target;
+
+// the first byte is the day, the rest is the puzzle input
+fuzz_target!(|data: &[u8]| {
+    if let Some((&day, input)) = data.split_first() {
+        if day == 9 && has_long_number(input) {
+            // day 9 moves the rope one step at a time, so the time spent follows the numbers
+            return;
+        }
+        if let Some(s) = solver(day as u32) {
+            let _ = s.solve_bytes(input, Parts::Both, &s.default_params());
+        }
+    }
+});
+
+fn has_long_number(input: &[u8]) -> bool {
+    input
+        .split(|b| !b.is_ascii_digit())
+        .any(|digits| digits.len() > 4)
+}
@@ -0,0 +1,218 @@
+use crate::generate::Rng;
+use crate::solver::{Params, Solver};
+use anyhow::{anyhow, Error};
+use std::any::Any;
+use std::fmt::Display;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+pub const DEFAULT_CASES: usize = 100;
+pub const DEFAULT_SIZE: usize = 50;
+
+#[derive(Debug)]
+pub struct DiffOptions {
+    pub cases: usize,
+    pub size: usize,
+    pub seed: u64,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+struct Disagreement {
+    part: u8,
+    expected: String,
+    actual: String,
+}
+
+// runs the solution and the reference on generated inputs, case `i` being generated
+// from seed `seed + i`, and reports the first disagreement on the smallest input that
+// still shows it; panics are caught and reported as answers, the panic hook is left
+// alone so they're still printed as they happen
+pub fn difftest<S: Solver>(
+    solver: &S,
+    day: u32,
+    options: &DiffOptions,
+    params: &Params,
+) -> Result<(), Error> {
+    for case in 0..options.cases {
+        let seed = options.seed.wrapping_add(case as u64);
+        let input = solver
+            .generate(&mut Rng::new(seed), options.size)
+            .ok_or_else(|| anyhow!("Day {day} has no input generator"))?;
+
+        let (compared, disagreement) = compare(solver, &input, params)
+            .map_err(|e| anyhow!("Seed {seed}: the generated input doesn't parse: {e}"))?;
+        if compared == 0 {
+            return Err(anyhow!("Day {day} has no reference implementation"));
+        }
+
+        if let Some(d) = disagreement {
+            let shrunk = shrink(
+                &input,
+                |s| matches!(compare(solver, s, params), Ok((_, Some(other))) if other.part == d.part),
+            );
+            // the answers for the shrunk input, which is what the reader will look at
+            let d = compare(solver, &shrunk, params)
+                .ok()
+                .and_then(|(_, d)| d)
+                .unwrap_or(d);
+
+            println!(
+                "Part {} disagrees with the reference on seed {seed}, shrunk from {} to {} lines:",
+                d.part,
+                input.lines().count(),
+                shrunk.lines().count()
+            );
+            print!("{shrunk}");
+            println!("reference: {}", d.expected);
+            println!("solution:  {}", d.actual);
+
+            return Err(anyhow!("Day {day}: difference found"));
+        }
+    }
+
+    println!(
+        "Day {day}: {} cases agree with the reference",
+        options.cases
+    );
+    Ok(())
+}
+
+// returns how many parts have a reference, and the first one that disagrees
+fn compare<S: Solver>(
+    solver: &S,
+    input: &str,
+    params: &Params,
+) -> Result<(usize, Option<Disagreement>), String> {
+    let input = catch_unwind(AssertUnwindSafe(|| {
+        let bytes = solver.read_input(input.as_bytes()).unwrap_or_default();
+        let input = solver.parse_input(bytes.as_slice())?;
+        solver.check_input(&input, params).map(|_| input)
+    }))
+    .map_err(panic_message)?
+    .map_err(|e| e.to_string())?;
+
+    let parts = [
+        (
+            outcome(|| solver.reference_first(&input, params)),
+            outcome(|| Some(solver.solve_first_with(&input, params))),
+        ),
+        (
+            outcome(|| solver.reference_second(&input, params)),
+            outcome(|| Some(solver.solve_second_with(&input, params))),
+        ),
+    ];
+
+    let mut compared = 0;
+    for (part, (expected, actual)) in (1..).zip(parts) {
+        if let (Some(expected), Some(actual)) = (expected, actual) {
+            compared += 1;
+            // both sides giving up on an input is agreeing on it
+            let agree = match (&expected, &actual) {
+                (Ok(e), Ok(a)) => e == a,
+                (e, a) => e.is_err() && a.is_err(),
+            };
+            if !agree {
+                let show = |r: Result<String, String>| r.unwrap_or_else(|e| format!("panic: {e}"));
+                return Ok((
+                    compared,
+                    Some(Disagreement {
+                        part,
+                        expected: show(expected),
+                        actual: show(actual),
+                    }),
+                ));
+            }
+        }
+    }
+
+    Ok((compared, None))
+}
+
+// a panic is an answer too, so that it can be compared with the other side
+fn outcome<T: Display>(f: impl FnOnce() -> Option<T>) -> Option<Result<String, String>> {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(value) => value.map(|v| Ok(v.to_string())),
+        Err(e) => Some(Err(panic_message(e))),
+    }
+}
+
+fn panic_message(e: Box<dyn Any + Send>) -> String {
+    e.downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| e.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+// removes as many lines, then as many characters within the remaining lines, as
+// possible while `fails` still holds
+pub fn shrink(input: &str, fails: impl Fn(&str) -> bool) -> String {
+    let join = |lines: &[String]| lines.iter().map(|l| format!("{l}\n")).collect::<String>();
+
+    let lines = input.lines().map(String::from).collect::<Vec<_>>();
+    let mut lines = remove_chunks(lines, |ls| fails(&join(ls)));
+
+    for i in 0..lines.len() {
+        let chars = lines[i].chars().collect::<Vec<_>>();
+        let chars = remove_chunks(chars, |cs| {
+            let mut candidate = lines.clone();
+            candidate[i] = cs.iter().collect();
+            fails(&join(&candidate))
+        });
+        lines[i] = chars.into_iter().collect();
+    }
+
+    join(&lines)
+}
+
+// delta debugging: removes chunks of halving sizes, until no single item can go
+fn remove_chunks<T: Clone>(mut items: Vec<T>, fails: impl Fn(&[T]) -> bool) -> Vec<T> {
+    let mut chunk = (items.len() / 2).max(1);
+
+    loop {
+        let mut removed = false;
+        let mut i = 0;
+        while i < items.len() {
+            let end = (i + chunk).min(items.len());
+            let candidate = [&items[..i], &items[end..]].concat();
+            if fails(&candidate) {
+                items = candidate;
+                removed = true;
+            } else {
+                i += chunk;
+            }
+        }
+
+        if chunk == 1 && !removed {
+            return items;
+        }
+        chunk = (chunk / 2).max(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solutions::solver;
+
+    #[test]
+    fn test_shrink() {
+        let input = (1..=10).map(|i| format!("{i}\n")).collect::<String>();
+        let shrunk = shrink(&input, |s| s.lines().any(|l| l == "3") && s.contains('7'));
+        assert_eq!(shrunk, "3\n7\n");
+
+        assert_eq!(shrink("abcdef\n", |s| s.contains("ce")), "ce\n");
+    }
+
+    #[test]
+    fn test_references_agree() {
+        let options = DiffOptions {
+            cases: 10,
+            size: 20,
+            seed: 0,
+        };
+
+        for day in [5, 6, 7, 8, 10, 12] {
+            let s = solver(day).unwrap();
+            assert!(s.difftest(day, &options, &s.default_params()).is_ok());
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use crate::solutions::solver;
+use anyhow::{anyhow, Error};
+use std::ops::Range;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const DEFAULT_SIZE: usize = 1000;
+
+// splitmix64, good enough for test inputs and fully determined by the seed
+#[derive(Clone, Debug)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn from_time() -> (Self, u64) {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos() as u64);
+        (Self::new(seed), seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    // uniform in the range, which must not be empty
+    pub fn range(&mut self, r: Range<u64>) -> u64 {
+        assert!(r.start < r.end, "empty range");
+        r.start + self.next_u64() % (r.end - r.start)
+    }
+
+    pub fn below(&mut self, n: usize) -> usize {
+        self.range(0..n as u64) as usize
+    }
+
+    pub fn chance(&mut self, p: f64) -> bool {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64 <= p
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+// prints a generated input for the day, and the seed to reproduce it
+pub fn generate(day: u32, size: usize, seed: Option<u64>) -> Result<(), Error> {
+    let s = solver(day).ok_or_else(|| anyhow!("Day {day} hasn't been solved yet :("))?;
+    let (mut rng, seed) = match seed {
+        Some(seed) => (Rng::new(seed), seed),
+        None => Rng::from_time(),
+    };
+
+    let input = s
+        .generate(&mut rng, size)
+        .ok_or_else(|| anyhow!("Day {day} has no input generator"))?;
+    eprintln!("Day {day}, size {size}, seed {seed}");
+    print!("{input}");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solutions::DAYS;
+    use crate::solver::Parts;
+
+    #[test]
+    fn test_rng() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        assert_eq!(a.next_u64(), b.next_u64());
+        assert!((0..1000).all(|_| (3..7).contains(&a.range(3..7))));
+    }
+
+    #[test]
+    fn test_generated_inputs_solve() {
+        for &day in DAYS {
+            let s = solver(day).unwrap();
+            for seed in 0..5 {
+                let input = s.generate(&mut Rng::new(seed), 50).unwrap();
+                let report = s
+                    .solve_bytes(input.as_bytes(), Parts::Both, &s.default_params())
+                    .unwrap();
+                assert_eq!(report.answers.len(), 2, "day {day}, seed {seed}");
+            }
+        }
+    }
+}
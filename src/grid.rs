@@ -2,17 +2,54 @@ use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::fmt::{Display, Error, Formatter};
 use std::io::{BufRead, BufReader, Read};
-use std::iter::{repeat, FromIterator};
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
 use std::str::FromStr;
 
-#[derive(Clone, Debug)]
+const OFFSETS4: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+const OFFSETS8: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+// why a grid couldn't be read
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum GridError<E> {
+    Cell(E),
+    Ragged {
+        line: usize,
+        len: usize,
+        expected: usize,
+    },
+}
+
+impl<E: Display> Display for GridError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            GridError::Cell(e) => write!(f, "{e}"),
+            GridError::Ragged {
+                line,
+                len,
+                expected,
+            } => write!(f, "line {line}: {len} cells, expected {expected}"),
+        }
+    }
+}
+
+impl<E: std::fmt::Debug + Display> std::error::Error for GridError<E> {}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Grid<T> {
     cells: Vec<T>,
     pub w: usize,
     pub h: usize,
 }
 
-#[allow(unused)]
 impl<T> Grid<T> {
     pub fn new(w: usize, h: usize) -> Self
     where
@@ -26,58 +63,74 @@ impl<T> Grid<T> {
         T: Clone,
     {
         Self {
-            cells: Vec::from_iter(repeat(val).take(w * h)),
+            cells: vec![val; w * h],
             w,
             h,
         }
     }
 
-    pub fn from_reader_callback<R, F, E>(r: R, f: F) -> Result<Self, E>
+    // each cell computed from its coordinates
+    pub fn from_fn(w: usize, h: usize, f: impl FnMut((usize, usize)) -> T) -> Self {
+        Self {
+            cells: coords(w, h).map(f).collect(),
+            w,
+            h,
+        }
+    }
+
+    pub fn from_reader_callback<R, F, E>(r: R, f: F) -> Result<Self, GridError<E>>
     where
         R: Read,
         F: FnMut(u8) -> Result<T, E> + Copy,
     {
-        let cells = BufReader::new(r)
+        let rows = BufReader::new(r)
             .lines()
-            .filter_map(|l| l.ok())
+            .map_while(Result::ok)
             .map(|l| l.bytes().map(f).collect::<Result<Vec<_>, _>>())
-            .collect::<Result<Vec<_>, _>>()?;
-        let h = cells.len();
-        let w = cells.first().map_or(0, |c| c.len());
-
-        Ok(Self {
-            cells: cells.into_iter().flatten().collect(),
-            w,
-            h,
-        })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(GridError::Cell)?;
+        Self::from_rows(rows)
     }
 
-    pub fn from_reader<R: Read>(r: R) -> Result<Self, T::Error>
+    pub fn from_reader<R: Read>(r: R) -> Result<Self, GridError<T::Error>>
     where
         T: TryFrom<u8>,
     {
         Self::from_reader_callback(r, T::try_from)
     }
 
-    pub fn from_split_whitespace_reader<R>(r: R) -> Result<Self, T::Err>
+    pub fn from_split_whitespace_reader<R>(r: R) -> Result<Self, GridError<T::Err>>
     where
         T: FromStr,
         R: Read,
     {
-        let cells = BufReader::new(r)
+        let rows = BufReader::new(r)
             .lines()
-            .filter_map(|l| l.ok())
+            .map_while(Result::ok)
             .map(|l| {
                 l.split_whitespace()
                     .map(T::from_str)
                     .collect::<Result<Vec<_>, _>>()
             })
-            .collect::<Result<Vec<_>, _>>()?;
-        let h = cells.len();
-        let w = cells.first().map_or(0, |c| c.len());
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(GridError::Cell)?;
+        Self::from_rows(rows)
+    }
+
+    // every row must be as long as the first one
+    fn from_rows<E>(rows: Vec<Vec<T>>) -> Result<Self, GridError<E>> {
+        let h = rows.len();
+        let w = rows.first().map_or(0, |r| r.len());
+        if let Some((i, row)) = rows.iter().enumerate().find(|(_, r)| r.len() != w) {
+            return Err(GridError::Ragged {
+                line: i + 1,
+                len: row.len(),
+                expected: w,
+            });
+        }
 
         Ok(Self {
-            cells: cells.into_iter().flatten().collect(),
+            cells: rows.into_iter().flatten().collect(),
             w,
             h,
         })
@@ -110,7 +163,7 @@ impl<T> Grid<T> {
         let x_offset = -min_x;
         let y_offset = -min_y;
 
-        let mut grid = Self::new(w as usize, h as usize);
+        let mut grid = Self::new(w, h);
 
         for (pt, cell) in points {
             let x = (pt.x + x_offset) as usize;
@@ -143,58 +196,81 @@ impl<T> Grid<T> {
         c.x() < self.w && c.y() < self.h
     }
 
-    pub fn neighbours4(&self, c: impl Coord) -> Vec<&T> {
+    pub fn neighbours4(&self, c: impl Coord) -> impl Iterator<Item = &T> + '_ {
+        self.neighbours_with_coords4(c).map(|(_, v)| v)
+    }
+
+    pub fn neighbours_coords4(&self, c: impl Coord) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.neighbours_at(c.coords(), &OFFSETS4)
+    }
+
+    pub fn neighbours_with_coords4(
+        &self,
+        c: impl Coord,
+    ) -> impl Iterator<Item = ((usize, usize), &T)> + '_ {
         self.neighbours_coords4(c)
-            .iter()
-            .flat_map(|&c| self.get(c))
-            .collect()
+            .map(|n| (n, self.get(n).expect("valid coord")))
     }
 
-    pub fn neighbours_coords4(&self, c: impl Coord) -> Vec<(usize, usize)> {
-        [(-1, 0), (0, -1), (0, 1), (1, 0)]
-            .iter()
-            .flat_map(|&(dx, dy)| self.neighbour_coords(&c, dx, dy))
-            .collect()
+    pub fn neighbours8(&self, c: impl Coord) -> impl Iterator<Item = &T> + '_ {
+        self.neighbours_with_coords8(c).map(|(_, v)| v)
     }
 
-    pub fn neighbours8(&self, c: impl Coord) -> Vec<&T> {
+    pub fn neighbours_coords8(&self, c: impl Coord) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.neighbours_at(c.coords(), &OFFSETS8)
+    }
+
+    pub fn neighbours_with_coords8(
+        &self,
+        c: impl Coord,
+    ) -> impl Iterator<Item = ((usize, usize), &T)> + '_ {
         self.neighbours_coords8(c)
+            .map(|n| (n, self.get(n).expect("valid coord")))
+    }
+
+    // calls `f` on each neighbour, which it can change
+    pub fn neighbours4_mut(&mut self, c: impl Coord, f: impl FnMut((usize, usize), &mut T)) {
+        self.neighbours_at_mut(c.coords(), &OFFSETS4, f)
+    }
+
+    pub fn neighbours8_mut(&mut self, c: impl Coord, f: impl FnMut((usize, usize), &mut T)) {
+        self.neighbours_at_mut(c.coords(), &OFFSETS8, f)
+    }
+
+    fn neighbours_at(
+        &self,
+        c: (usize, usize),
+        offsets: &'static [(isize, isize)],
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        offsets
             .iter()
-            .flat_map(|&c| self.get(c))
-            .collect()
-    }
-
-    pub fn neighbours_coords8(&self, c: impl Coord) -> Vec<(usize, usize)> {
-        [
-            (-1, -1),
-            (-1, 0),
-            (-1, 1),
-            (0, -1),
-            (0, 1),
-            (1, -1),
-            (1, 0),
-            (1, 1),
-        ]
-        .iter()
-        .flat_map(|&(dx, dy)| self.neighbour_coords(&c, dx, dy))
-        .collect()
+            .flat_map(move |&(dx, dy)| self.neighbour_coords(&c, dx, dy))
     }
 
-    fn neighbour_coords(&self, c: &impl Coord, dx: isize, dy: isize) -> Option<(usize, usize)> {
-        if (c.x() == 0 && dx == -1)
-            || (c.y() == 0 && dy == -1)
-            || (c.x() == self.w - 1 && dx == 1)
-            || (c.y() == self.h - 1 && dy == 1)
-        {
-            None
-        } else {
-            Some((
-                ((c.x() as isize) + dx) as usize,
-                ((c.y() as isize) + dy) as usize,
-            ))
+    fn neighbours_at_mut(
+        &mut self,
+        c: (usize, usize),
+        offsets: &[(isize, isize)],
+        mut f: impl FnMut((usize, usize), &mut T),
+    ) {
+        for &(dx, dy) in offsets {
+            if let Some(n) = self.neighbour_coords(&c, dx, dy) {
+                f(n, self.get_mut(n).expect("valid coord"));
+            }
         }
     }
 
+    // the next cell in the direction, if it's still on the grid
+    pub fn step(&self, c: impl Coord, dir: impl Into<Direction8>) -> Option<(usize, usize)> {
+        let d = dir.into().delta();
+        self.neighbour_coords(&c, d.x as isize, d.y as isize)
+    }
+
+    fn neighbour_coords(&self, c: &impl Coord, dx: isize, dy: isize) -> Option<(usize, usize)> {
+        let n = (c.x().checked_add_signed(dx)?, c.y().checked_add_signed(dy)?);
+        self.contains_coord(&n).then_some(n)
+    }
+
     pub fn iter_row(&self, row: usize) -> RowIter<'_, T> {
         RowIter {
             grid: self,
@@ -211,20 +287,308 @@ impl<T> Grid<T> {
         }
     }
 
+    // the cells met going from `from` in the direction up to the edge, `from` excluded
+    pub fn ray(&self, from: impl Coord, dir: impl Into<Direction8>) -> Ray<'_, T> {
+        let (x, y) = from.coords();
+        let delta = dir.into().delta();
+        let room = |pos: usize, len: usize, d: i64| match d {
+            1 => len.saturating_sub(pos + 1),
+            -1 => pos,
+            _ => usize::MAX,
+        };
+        let len = if self.contains_coord(&(x, y)) {
+            room(x, self.w, delta.x).min(room(y, self.h, delta.y))
+        } else {
+            0
+        };
+
+        Ray {
+            grid: self,
+            from: (x, y),
+            delta: (delta.x as isize, delta.y as isize),
+            front: 1,
+            back: len,
+        }
+    }
+
     pub fn as_slice(&self) -> &[T] {
         &self.cells
     }
 
+    // the cells and their coordinates below are all row by row, from the top left corner
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.cells.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, T> {
+        self.cells.iter_mut()
+    }
+
+    pub fn iter_coords(&self) -> impl Iterator<Item = (usize, usize)> {
+        coords(self.w, self.h)
+    }
+
+    pub fn enumerate(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.iter_coords().zip(self.cells.iter())
+    }
+
+    pub fn positions<'a>(
+        &'a self,
+        mut pred: impl FnMut(&T) -> bool + 'a,
+    ) -> impl Iterator<Item = (usize, usize)> + 'a {
+        self.enumerate()
+            .filter(move |(_, v)| pred(v))
+            .map(|(c, _)| c)
+    }
+
+    pub fn find(&self, pred: impl FnMut(&T) -> bool) -> Option<(usize, usize)> {
+        self.positions(pred).next()
+    }
+
+    pub fn count(&self, mut pred: impl FnMut(&T) -> bool) -> usize {
+        self.cells.iter().filter(|v| pred(v)).count()
+    }
+
+    pub fn map<U>(&self, mut f: impl FnMut((usize, usize), &T) -> U) -> Grid<U> {
+        Grid {
+            cells: self.enumerate().map(|(c, v)| f(c, v)).collect(),
+            w: self.w,
+            h: self.h,
+        }
+    }
+
     pub fn as_slice_mut(&mut self) -> &mut [T] {
         &mut self.cells
     }
+
+    // the transforms build a new grid from the cell each coordinate comes from
+    fn remap(&self, w: usize, h: usize, from: impl Fn(usize, usize) -> (usize, usize)) -> Self
+    where
+        T: Clone,
+    {
+        Self::from_fn(w, h, |(x, y)| {
+            self.get(from(x, y)).expect("valid coord").clone()
+        })
+    }
+
+    // the first column becomes the first row
+    pub fn transpose(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.remap(self.h, self.w, |x, y| (y, x))
+    }
+
+    // clockwise, the first column becomes the first row read backwards
+    pub fn rotate_cw(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.remap(self.h, self.w, |x, y| (y, self.h - 1 - x))
+    }
+
+    pub fn rotate_ccw(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.remap(self.h, self.w, |x, y| (self.w - 1 - y, x))
+    }
+
+    // mirrors left and right
+    pub fn flip_h(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.remap(self.w, self.h, |x, y| (self.w - 1 - x, y))
+    }
+
+    // mirrors top and bottom
+    pub fn flip_v(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.remap(self.w, self.h, |x, y| (x, self.h - 1 - y))
+    }
+
+    // a `w` by `h` rectangle with its top left corner at `from`, none if it doesn't fit
+    pub fn view(&self, from: impl Coord, w: usize, h: usize) -> Option<SubGrid<'_, T>> {
+        let (x, y) = from.coords();
+        let fits = x.checked_add(w).is_some_and(|r| r <= self.w)
+            && y.checked_add(h).is_some_and(|b| b <= self.h);
+        fits.then_some(SubGrid {
+            grid: self,
+            x,
+            y,
+            w,
+            h,
+        })
+    }
+
+    pub fn crop(&self, from: impl Coord, w: usize, h: usize) -> Option<Self>
+    where
+        T: Clone,
+    {
+        self.view(from, w, h).map(|v| v.to_grid())
+    }
+
+    fn neighbours_coords(
+        &self,
+        c: impl Coord,
+        conn: Connectivity,
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let offsets: &'static [(isize, isize)] = match conn {
+            Connectivity::Four => &OFFSETS4,
+            Connectivity::Eight => &OFFSETS8,
+        };
+        self.neighbours_at(c.coords(), offsets)
+    }
+
+    // marks and returns the cells reached from the starts, moving from a cell to the next
+    // when `spreads` allows it
+    fn spread(
+        &self,
+        starts: Vec<(usize, usize)>,
+        conn: Connectivity,
+        seen: &mut Grid<bool>,
+        spreads: impl Fn(&T, &T) -> bool,
+    ) -> Vec<(usize, usize)> {
+        let mut reached = vec![];
+        let mut stack = starts;
+        for &c in &stack {
+            *seen.get_mut(c).expect("valid coord") = true;
+        }
+
+        while let Some(c) = stack.pop() {
+            reached.push(c);
+            let from = self.get(c).expect("valid coord");
+            for n in self.neighbours_coords(c, conn) {
+                let seen = seen.get_mut(n).expect("valid coord");
+                if !*seen && spreads(from, self.get(n).expect("valid coord")) {
+                    *seen = true;
+                    stack.push(n);
+                }
+            }
+        }
+
+        reached
+    }
+
+    // the cells connected to `from` through matching cells, none if `from` doesn't match
+    pub fn flood_fill(
+        &self,
+        from: impl Coord,
+        conn: Connectivity,
+        matches: impl Fn(&T) -> bool,
+    ) -> Vec<(usize, usize)> {
+        let from = from.coords();
+        if !self.get(from).is_some_and(&matches) {
+            return vec![];
+        }
+        let mut seen = Grid::new_with(self.w, self.h, false);
+        self.spread(vec![from], conn, &mut seen, |_, to| matches(to))
+    }
+
+    // labels every cell with its component, neighbours being in the same one when
+    // `connected` holds for them, e.g. when they have the same value
+    pub fn components(&self, conn: Connectivity, connected: impl Fn(&T, &T) -> bool) -> Components {
+        let mut seen = Grid::new_with(self.w, self.h, false);
+        let mut labels = Grid::new_with(self.w, self.h, 0);
+        let mut components = vec![];
+
+        for c in self.iter_coords() {
+            if *seen.get(c).expect("valid coord") {
+                continue;
+            }
+            let cells = self.spread(vec![c], conn, &mut seen, &connected);
+            let mut component = Component {
+                size: cells.len(),
+                min: c,
+                max: c,
+            };
+            for &(x, y) in &cells {
+                *labels.get_mut((x, y)).expect("valid coord") = components.len();
+                component.min = (component.min.0.min(x), component.min.1.min(y));
+                component.max = (component.max.0.max(x), component.max.1.max(y));
+            }
+            components.push(component);
+        }
+
+        Components { labels, components }
+    }
+
+    // the matching cells that can't be reached from the border through matching cells
+    pub fn enclosed(&self, conn: Connectivity, open: impl Fn(&T) -> bool) -> Grid<bool> {
+        let border = self
+            .iter_coords()
+            .filter(|&(x, y)| x == 0 || y == 0 || x + 1 == self.w || y + 1 == self.h)
+            .filter(|&c| self.get(c).is_some_and(&open))
+            .collect();
+        let mut outside = Grid::new_with(self.w, self.h, false);
+        self.spread(border, conn, &mut outside, |_, to| open(to));
+
+        Grid::from_fn(self.w, self.h, |c| {
+            open(self.get(c).expect("valid coord")) && !outside.get(c).expect("valid coord")
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Connectivity {
+    Four,
+    Eight,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Component {
+    pub size: usize,
+    // the bounding box, both corners included
+    pub min: (usize, usize),
+    pub max: (usize, usize),
+}
+
+// components are numbered from 0 in the order their top left cell comes in the grid
+#[derive(Clone, Debug)]
+pub struct Components {
+    pub labels: Grid<usize>,
+    pub components: Vec<Component>,
+}
+
+fn coords(w: usize, h: usize) -> impl Iterator<Item = (usize, usize)> {
+    (0..h).flat_map(move |y| (0..w).map(move |x| (x, y)))
+}
+
+impl<T> IntoIterator for Grid<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.cells.into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Grid<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut Grid<T> {
+    type Item = &'a mut T;
+    type IntoIter = std::slice::IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
 }
 
 impl<T> FromStr for Grid<T>
 where
     T: TryFrom<u8>,
 {
-    type Err = T::Error;
+    type Err = GridError<T::Error>;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Grid::from_reader(s.as_bytes())
@@ -236,7 +600,7 @@ where
     T: Display,
 {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
-        for row in self.cells.chunks(self.w) {
+        for row in self.cells.chunks(self.w.max(1)) {
             for cell in row {
                 write!(f, "{}", cell)?;
             }
@@ -308,10 +672,525 @@ impl Coord for &(usize, usize) {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
 pub struct Point {
-    x: i64,
-    y: i64,
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Point {
+    pub const ORIGIN: Point = Point { x: 0, y: 0 };
+
+    pub const fn new(x: i64, y: i64) -> Self {
+        Self { x, y }
+    }
+
+    // a step of at most one along each axis, towards the point
+    pub fn signum(self) -> Self {
+        Self::new(self.x.signum(), self.y.signum())
+    }
+
+    pub fn manhattan(self, other: Self) -> u64 {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+
+    // the number of king moves between the points
+    pub fn chebyshev(self, other: Self) -> u64 {
+        self.x.abs_diff(other.x).max(self.y.abs_diff(other.y))
+    }
+
+    // clockwise with y going down, like on the screen
+    pub fn rotate_right(self) -> Self {
+        Self::new(-self.y, self.x)
+    }
+
+    pub fn rotate_left(self) -> Self {
+        Self::new(self.y, -self.x)
+    }
+
+    pub fn from_coord(c: &impl Coord) -> Option<Self> {
+        Some(Self::new(c.x().try_into().ok()?, c.y().try_into().ok()?))
+    }
+
+    // None for the points left of or above the origin
+    pub fn to_coord(self) -> Option<(usize, usize)> {
+        Some((self.x.try_into().ok()?, self.y.try_into().ok()?))
+    }
+}
+
+impl From<(i64, i64)> for Point {
+    fn from((x, y): (i64, i64)) -> Self {
+        Self::new(x, y)
+    }
+}
+
+impl TryFrom<(usize, usize)> for Point {
+    type Error = &'static str;
+
+    fn try_from(c: (usize, usize)) -> Result<Self, Self::Error> {
+        Self::from_coord(&c).ok_or("coordinates too large for a point")
+    }
+}
+
+impl TryFrom<Point> for (usize, usize) {
+    type Error = &'static str;
+
+    fn try_from(p: Point) -> Result<Self, Self::Error> {
+        p.to_coord().ok_or("negative coordinates")
+    }
+}
+
+impl Add for Point {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl AddAssign for Point {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Point {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl SubAssign for Point {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl Mul<i64> for Point {
+    type Output = Self;
+
+    fn mul(self, rhs: i64) -> Self {
+        Self::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+impl Neg for Point {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.x, -self.y)
+    }
+}
+
+// y goes down, as in the grids read from the inputs
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    // clockwise from up
+    pub const ALL: [Direction; 4] = [Self::Up, Self::Right, Self::Down, Self::Left];
+
+    pub fn delta(self) -> Point {
+        match self {
+            Self::Up => Point::new(0, -1),
+            Self::Right => Point::new(1, 0),
+            Self::Down => Point::new(0, 1),
+            Self::Left => Point::new(-1, 0),
+        }
+    }
+
+    pub fn turn_right(self) -> Self {
+        Self::ALL[(self as usize + 1) % 4]
+    }
+
+    pub fn turn_left(self) -> Self {
+        Self::ALL[(self as usize + 3) % 4]
+    }
+
+    pub fn opposite(self) -> Self {
+        Self::ALL[(self as usize + 2) % 4]
+    }
+
+    pub fn arrow(self) -> char {
+        match self {
+            Self::Up => '^',
+            Self::Right => '>',
+            Self::Down => 'v',
+            Self::Left => '<',
+        }
+    }
+}
+
+// U/D/L/R, N/E/S/W or ^>v<
+impl FromStr for Direction {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "U" | "N" | "^" => Ok(Self::Up),
+            "R" | "E" | ">" => Ok(Self::Right),
+            "D" | "S" | "v" => Ok(Self::Down),
+            "L" | "W" | "<" => Ok(Self::Left),
+            _ => Err("invalid direction"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Direction8 {
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+impl Direction8 {
+    // clockwise from up
+    pub const ALL: [Direction8; 8] = [
+        Self::Up,
+        Self::UpRight,
+        Self::Right,
+        Self::DownRight,
+        Self::Down,
+        Self::DownLeft,
+        Self::Left,
+        Self::UpLeft,
+    ];
+
+    pub fn delta(self) -> Point {
+        match self {
+            Self::Up => Point::new(0, -1),
+            Self::UpRight => Point::new(1, -1),
+            Self::Right => Point::new(1, 0),
+            Self::DownRight => Point::new(1, 1),
+            Self::Down => Point::new(0, 1),
+            Self::DownLeft => Point::new(-1, 1),
+            Self::Left => Point::new(-1, 0),
+            Self::UpLeft => Point::new(-1, -1),
+        }
+    }
+
+    // by 45 degrees
+    pub fn turn_right(self) -> Self {
+        Self::ALL[(self as usize + 1) % 8]
+    }
+
+    pub fn turn_left(self) -> Self {
+        Self::ALL[(self as usize + 7) % 8]
+    }
+
+    pub fn opposite(self) -> Self {
+        Self::ALL[(self as usize + 4) % 8]
+    }
+}
+
+impl From<Direction> for Direction8 {
+    fn from(d: Direction) -> Self {
+        match d {
+            Direction::Up => Self::Up,
+            Direction::Right => Self::Right,
+            Direction::Down => Self::Down,
+            Direction::Left => Self::Left,
+        }
+    }
+}
+
+// the four directions, or NE/SE/SW/NW
+impl FromStr for Direction8 {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "NE" => Ok(Self::UpRight),
+            "SE" => Ok(Self::DownRight),
+            "SW" => Ok(Self::DownLeft),
+            "NW" => Ok(Self::UpLeft),
+            _ => s.parse::<Direction>().map(Self::from),
+        }
+    }
+}
+
+// a plane without bounds, only storing the cells that were set
+#[derive(Clone, Debug)]
+pub struct SparseGrid<T> {
+    cells: HashMap<Point, T>,
+    bounds: Option<(Point, Point)>,
+    empty: char,
+}
+
+impl<T> Default for SparseGrid<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new() -> Self {
+        Self {
+            cells: HashMap::new(),
+            bounds: None,
+            empty: '.',
+        }
+    }
+
+    // what `Display` draws where there's no cell
+    pub fn with_empty(mut self, empty: char) -> Self {
+        self.empty = empty;
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn get(&self, p: Point) -> Option<&T> {
+        self.cells.get(&p)
+    }
+
+    pub fn get_mut(&mut self, p: Point) -> Option<&mut T> {
+        self.cells.get_mut(&p)
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        self.cells.contains_key(&p)
+    }
+
+    pub fn insert(&mut self, p: Point, val: T) -> Option<T> {
+        self.bounds = Some(match self.bounds {
+            Some((min, max)) => (
+                Point::new(min.x.min(p.x), min.y.min(p.y)),
+                Point::new(max.x.max(p.x), max.y.max(p.y)),
+            ),
+            None => (p, p),
+        });
+        self.cells.insert(p, val)
+    }
+
+    // the smallest and largest coordinates ever inserted, both inclusive
+    pub fn bounds(&self) -> Option<(Point, Point)> {
+        self.bounds
+    }
+
+    pub fn neighbours4(&self, p: Point) -> impl Iterator<Item = &T> + '_ {
+        Self::neighbours_coords4(p).flat_map(|n| self.get(n))
+    }
+
+    pub fn neighbours_coords4(p: Point) -> impl Iterator<Item = Point> {
+        Self::offset_all(p, &OFFSETS4)
+    }
+
+    pub fn neighbours8(&self, p: Point) -> impl Iterator<Item = &T> + '_ {
+        Self::neighbours_coords8(p).flat_map(|n| self.get(n))
+    }
+
+    pub fn neighbours_coords8(p: Point) -> impl Iterator<Item = Point> {
+        Self::offset_all(p, &OFFSETS8)
+    }
+
+    fn offset_all(p: Point, offsets: &'static [(isize, isize)]) -> impl Iterator<Item = Point> {
+        offsets
+            .iter()
+            .map(move |&(dx, dy)| p + Point::new(dx as i64, dy as i64))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.cells.iter().map(|(&p, v)| (p, v))
+    }
+
+    // the bounds as a dense grid, its top left corner at their minimum, with `empty`
+    // where nothing was set
+    pub fn to_grid(&self, empty: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let Some((min, max)) = self.bounds else {
+            return Grid::new_with(0, 0, empty);
+        };
+        let mut grid = Grid::new_with(
+            (max.x - min.x + 1) as usize,
+            (max.y - min.y + 1) as usize,
+            empty,
+        );
+        for (p, v) in self.iter() {
+            if let Some(cell) = (p - min).to_coord().and_then(|c| grid.get_mut(c)) {
+                *cell = v.clone();
+            }
+        }
+        grid
+    }
+}
+
+impl<T> Display for SparseGrid<T>
+where
+    T: Display,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        if let Some((min, max)) = self.bounds {
+            for y in min.y..=max.y {
+                for x in min.x..=max.x {
+                    match self.get(Point { x, y }) {
+                        Some(v) => write!(f, "{v}")?,
+                        None => write!(f, "{}", self.empty)?,
+                    }
+                }
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+pub struct Ray<'a, T> {
+    grid: &'a Grid<T>,
+    from: (usize, usize),
+    delta: (isize, isize),
+    // the steps left to take, both included
+    front: usize,
+    back: usize,
+}
+
+impl<'a, T> Ray<'a, T> {
+    fn at(&self, steps: usize) -> ((usize, usize), &'a T) {
+        let c = (
+            self.from
+                .0
+                .wrapping_add_signed(self.delta.0 * steps as isize),
+            self.from
+                .1
+                .wrapping_add_signed(self.delta.1 * steps as isize),
+        );
+        (c, self.grid.get(c).expect("ray within the grid"))
+    }
+
+    // stops after the first cell that blocks the view, which is still yielded
+    pub fn take_until_blocking<F>(
+        self,
+        mut blocking: F,
+    ) -> impl Iterator<Item = ((usize, usize), &'a T)>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.scan(false, move |blocked, (c, v)| {
+            if *blocked {
+                return None;
+            }
+            *blocked = blocking(v);
+            Some((c, v))
+        })
+    }
+}
+
+impl<'a, T> Iterator for Ray<'a, T> {
+    type Item = ((usize, usize), &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front > self.back {
+            return None;
+        }
+        self.front += 1;
+        Some(self.at(self.front - 1))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.back + 1).saturating_sub(self.front);
+        (len, Some(len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Ray<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front > self.back {
+            return None;
+        }
+        self.back -= 1;
+        Some(self.at(self.back + 1))
+    }
+}
+
+impl<T> ExactSizeIterator for Ray<'_, T> {}
+
+// a rectangle borrowed from a grid, with its own coordinates
+#[derive(Debug)]
+pub struct SubGrid<'a, T> {
+    grid: &'a Grid<T>,
+    x: usize,
+    y: usize,
+    pub w: usize,
+    pub h: usize,
+}
+
+// copied whatever the cells are, it's only a reference
+impl<T> Clone for SubGrid<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for SubGrid<'_, T> {}
+
+impl<'a, T> SubGrid<'a, T> {
+    pub fn get(&self, c: impl Coord) -> Option<&'a T> {
+        if c.x() < self.w && c.y() < self.h {
+            self.grid.get((self.x + c.x(), self.y + c.y()))
+        } else {
+            None
+        }
+    }
+
+    pub fn iter_row(&self, row: usize) -> impl Iterator<Item = &'a T> {
+        let view = *self;
+        (0..self.w).flat_map(move |x| view.get((x, row)))
+    }
+
+    pub fn iter_col(&self, col: usize) -> impl Iterator<Item = &'a T> {
+        let view = *self;
+        (0..self.h).flat_map(move |y| view.get((col, y)))
+    }
+
+    // row by row
+    pub fn iter(&self) -> impl Iterator<Item = &'a T> {
+        let view = *self;
+        (0..self.h).flat_map(move |y| view.iter_row(y))
+    }
+
+    pub fn to_grid(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid::from_fn(self.w, self.h, |c| {
+            self.get(c).expect("valid coord").clone()
+        })
+    }
+}
+
+impl<T> Display for SubGrid<'_, T>
+where
+    T: Display,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        for y in 0..self.h {
+            for cell in self.iter_row(y) {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
 }
 
 pub struct ColIter<'a, T> {
@@ -345,3 +1224,236 @@ impl<'a, T> Iterator for RowIter<'a, T> {
         Some(val)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::assert_snapshot;
+
+    #[test]
+    fn test_ragged() {
+        let ragged = "123\n45\n6789\n".parse::<Grid<u8>>();
+        assert_eq!(
+            ragged,
+            Err(GridError::Ragged {
+                line: 2,
+                len: 2,
+                expected: 3
+            })
+        );
+        let ragged = Grid::<u32>::from_split_whitespace_reader("1 2\n3 4 5\n".as_bytes());
+        assert!(matches!(ragged, Err(GridError::Ragged { line: 2, .. })));
+
+        let grid = Grid::<u32>::from_split_whitespace_reader("1 2\n3 4\n".as_bytes()).unwrap();
+        assert_eq!((grid.w, grid.h), (2, 2));
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid: Grid<u8> = "abc\ndef\n".parse().unwrap();
+        let coords = grid.neighbours_coords4((0, 0)).collect::<Vec<_>>();
+        assert_eq!(coords, vec![(0, 1), (1, 0)]);
+        let values = grid.neighbours4((1, 1)).collect::<Vec<_>>();
+        assert_eq!(values, vec![&b'd', &b'b', &b'f']);
+        let pairs = grid.neighbours_with_coords8((2, 0)).collect::<Vec<_>>();
+        assert_eq!(
+            pairs,
+            vec![((1, 0), &b'b'), ((1, 1), &b'e'), ((2, 1), &b'f')]
+        );
+
+        let empty: Grid<u8> = "".parse().unwrap();
+        assert_eq!(empty.neighbours_coords8((0, 0)).count(), 0);
+
+        let mut grid = grid;
+        grid.neighbours4_mut((0, 1), |_, v| *v = v.to_ascii_uppercase());
+        assert_eq!(grid.as_slice(), b"AbcdEf");
+        assert_eq!(empty.to_string(), "");
+    }
+
+    #[test]
+    fn test_display() {
+        let mut grid = Grid::new_with(5, 3, '.');
+        grid.neighbours8_mut((3, 1), |_, v| *v = '#');
+        *grid.get_mut((3, 1)).unwrap() = 'x';
+        assert_snapshot("grid_display", &grid.to_string());
+    }
+
+    #[test]
+    fn test_point() {
+        let (a, b) = (Point::new(1, -2), Point::new(-3, 4));
+        assert_eq!(a + b, Point::new(-2, 2));
+        assert_eq!(a - b, Point::new(4, -6));
+        assert_eq!(-a * 3, Point::new(-3, 6));
+        assert_eq!((b - a).signum(), Point::new(-1, 1));
+        assert_eq!((a.manhattan(b), a.chebyshev(b)), (10, 6));
+
+        // right, down, left, up
+        let right = Point::new(1, 0);
+        assert_eq!(right.rotate_right(), Point::new(0, 1));
+        assert_eq!(right.rotate_right().rotate_right(), -right);
+        assert_eq!(right.rotate_left().rotate_right(), right);
+
+        assert_eq!(Point::try_from((2usize, 3)), Ok(Point::new(2, 3)));
+        assert!(Point::try_from((usize::MAX, 0)).is_err());
+        assert_eq!(<(usize, usize)>::try_from(Point::new(2, 3)), Ok((2, 3)));
+        assert!(a.to_coord().is_none());
+    }
+
+    #[test]
+    fn test_direction() {
+        assert_eq!(Direction::Up.turn_right(), Direction::Right);
+        assert_eq!(Direction::Up.turn_left(), Direction::Left);
+        assert_eq!(Direction::Left.opposite(), Direction::Right);
+        assert!(Direction::ALL
+            .iter()
+            .all(|&d| d.turn_right().delta() == d.delta().rotate_right()));
+        assert_eq!(Direction8::UpLeft.turn_right(), Direction8::Up);
+        assert_eq!(Direction8::DownRight.opposite(), Direction8::UpLeft);
+
+        let parsed = ["U", "E", "v", "<"].map(|s| s.parse::<Direction>().unwrap());
+        assert_eq!(parsed, Direction::ALL);
+        assert_eq!("SW".parse(), Ok(Direction8::DownLeft));
+        assert!("X".parse::<Direction>().is_err());
+
+        let grid: Grid<u8> = "ab\ncd\n".parse().unwrap();
+        assert_eq!(grid.step((0, 0), Direction::Down), Some((0, 1)));
+        assert_eq!(grid.step((0, 0), Direction8::DownRight), Some((1, 1)));
+        assert_eq!(grid.step((0, 0), Direction::Left), None);
+        assert_eq!(grid.step((1, 1), Direction::Right), None);
+    }
+
+    #[test]
+    fn test_ray() {
+        let grid: Grid<u8> = "abcd\nefgh\nijkl\n".parse().unwrap();
+        fn values<'a>(ray: impl Iterator<Item = ((usize, usize), &'a u8)>) -> String {
+            ray.map(|(_, &v)| v as char).collect()
+        }
+
+        assert_eq!(values(grid.ray((1, 1), Direction::Right)), "gh");
+        assert_eq!(values(grid.ray((1, 1), Direction::Up)), "b");
+        assert_eq!(values(grid.ray((0, 0), Direction8::DownRight)), "fk");
+        assert_eq!(values(grid.ray((0, 1), Direction::Left)), "");
+        assert_eq!(values(grid.ray((9, 9), Direction::Left)), "");
+        assert_eq!(values(grid.ray((0, 1), Direction::Right).rev()), "hgf");
+        assert_eq!(grid.ray((0, 0), Direction::Down).len(), 2);
+
+        let mut ray = grid.ray((0, 0), Direction::Right);
+        assert_eq!(ray.next(), Some(((1, 0), &b'b')));
+        assert_eq!(ray.next_back(), Some(((3, 0), &b'd')));
+        assert_eq!(ray.next_back(), Some(((2, 0), &b'c')));
+        assert_eq!(ray.next(), None);
+
+        let seen = grid
+            .ray((0, 0), Direction::Right)
+            .take_until_blocking(|&v| v >= b'c');
+        assert_eq!(
+            seen.map(|(c, _)| c).collect::<Vec<_>>(),
+            vec![(1, 0), (2, 0)]
+        );
+    }
+
+    #[test]
+    fn test_coords() {
+        let mut grid: Grid<char> = "ab\ncb\n".parse().unwrap();
+        let coords = grid.iter_coords().collect::<Vec<_>>();
+        assert_eq!(coords, vec![(0, 0), (1, 0), (0, 1), (1, 1)]);
+        assert_eq!(grid.enumerate().nth(2), Some(((0, 1), &'c')));
+
+        assert_eq!(
+            grid.positions(|&v| v == 'b').collect::<Vec<_>>(),
+            vec![(1, 0), (1, 1)]
+        );
+        assert_eq!(grid.find(|&v| v == 'c'), Some((0, 1)));
+        assert_eq!(grid.find(|&v| v == 'z'), None);
+        assert_eq!(grid.count(|&v| v == 'b'), 2);
+
+        let sums = grid.map(|(x, y), _| x + y);
+        assert_eq!(sums.as_slice(), &[0, 1, 1, 2]);
+
+        for v in &mut grid {
+            *v = v.to_ascii_uppercase();
+        }
+        assert_eq!((&grid).into_iter().collect::<String>(), "ABCB");
+        assert_eq!(grid.into_iter().collect::<String>(), "ABCB");
+    }
+
+    #[test]
+    fn test_transforms() {
+        let grid: Grid<char> = "abc\ndef\n".parse().unwrap();
+        let text = |g: Grid<char>| g.to_string();
+
+        assert_eq!(text(grid.transpose()), "ad\nbe\ncf\n");
+        assert_eq!(text(grid.rotate_cw()), "da\neb\nfc\n");
+        assert_eq!(text(grid.rotate_ccw()), "cf\nbe\nad\n");
+        assert_eq!(text(grid.flip_h()), "cba\nfed\n");
+        assert_eq!(text(grid.flip_v()), "def\nabc\n");
+
+        let turned = (0..4).fold(grid.clone(), |g, _| g.rotate_cw());
+        assert_eq!(turned, grid);
+        assert_eq!(grid.rotate_cw(), grid.transpose().flip_h());
+        assert_eq!(grid.rotate_ccw().rotate_cw(), grid);
+    }
+
+    #[test]
+    fn test_view() {
+        let grid: Grid<char> = "abcd\nefgh\nijkl\n".parse().unwrap();
+
+        let view = grid.view((1, 1), 2, 2).unwrap();
+        assert_eq!(view.get((0, 0)), Some(&'f'));
+        assert_eq!(view.get((2, 0)), None);
+        assert_eq!(view.iter().collect::<String>(), "fgjk");
+        assert_eq!(view.iter_col(1).collect::<String>(), "gk");
+        assert_eq!(view.to_string(), "fg\njk\n");
+
+        assert!(grid.view((3, 0), 2, 1).is_none());
+        assert_eq!(grid.crop((2, 0), 2, 3).unwrap().to_string(), "cd\ngh\nkl\n");
+        assert_eq!(grid.crop((0, 0), 0, 0).unwrap().w, 0);
+    }
+
+    #[test]
+    fn test_regions() {
+        let grid: Grid<char> = "##...\n#.#..\n.##.#\n....#\n".parse().unwrap();
+        let wall = |&c: &char| c == '#';
+        let mut filled = grid.flood_fill((0, 0), Connectivity::Four, wall);
+        filled.sort();
+        assert_eq!(filled, vec![(0, 0), (0, 1), (1, 0)]);
+        assert_eq!(grid.flood_fill((0, 0), Connectivity::Eight, wall).len(), 6);
+        assert!(grid.flood_fill((2, 0), Connectivity::Four, wall).is_empty());
+
+        let walls = grid.components(Connectivity::Four, |a, b| a == b);
+        let sizes = walls.components.iter().map(|c| c.size).collect::<Vec<_>>();
+        assert_eq!(sizes, vec![3, 11, 1, 3, 2]);
+        assert_eq!(walls.labels.get((4, 3)), Some(&4));
+        assert_eq!(walls.components[3].min, (1, 1));
+        assert_eq!(walls.components[3].max, (2, 2));
+
+        let enclosed = grid.enclosed(Connectivity::Four, |&c| c == '.');
+        assert_eq!(enclosed.get((1, 1)), Some(&true));
+        assert_eq!(enclosed.count(|&e| e), 1);
+    }
+
+    #[test]
+    fn test_sparse_grid() {
+        let mut sparse = SparseGrid::new().with_empty(' ');
+        assert_eq!(sparse.bounds(), None);
+        assert_eq!(sparse.to_string(), "");
+
+        sparse.insert(Point { x: -2, y: 1 }, 'a');
+        sparse.insert(Point { x: 1, y: -1 }, 'b');
+        assert_eq!(sparse.insert(Point { x: 1, y: -1 }, 'c'), Some('b'));
+        assert_eq!(sparse.len(), 2);
+        assert_eq!(
+            sparse.bounds(),
+            Some((Point { x: -2, y: -1 }, Point { x: 1, y: 1 }))
+        );
+        assert_eq!(
+            sparse.neighbours8(Point { x: 0, y: 0 }).collect::<Vec<_>>(),
+            vec![&'c']
+        );
+        assert_eq!(sparse.to_string(), "   c\n    \na   \n");
+
+        let grid = sparse.to_grid('.');
+        assert_eq!((grid.w, grid.h), (4, 3));
+        assert_eq!(grid.to_string(), "...c\n....\na...\n");
+    }
+}
@@ -0,0 +1,12 @@
+pub mod difftest;
+pub mod generate;
+pub mod grid;
+pub mod repl;
+pub mod search;
+pub mod serve;
+pub mod snapshot;
+pub mod solutions;
+pub mod solver;
+pub mod trace;
+pub mod visualize;
+pub mod watch;
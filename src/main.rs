@@ -1,15 +1,152 @@
-use crate::solutions::exec_day;
+use anyhow::{anyhow, Error};
+use aoc_rs_2022::difftest::DiffOptions;
+use aoc_rs_2022::generate::Rng;
+use aoc_rs_2022::solutions::solver;
+use aoc_rs_2022::solver::{Params, Runner};
+use aoc_rs_2022::visualize::{FrameDump, TerminalPlayer, Visualizer};
+use aoc_rs_2022::{difftest, generate, serve, trace, visualize, watch};
 use std::env;
+use std::process;
 
-mod grid;
-mod solutions;
-mod solver;
+#[derive(Default)]
+struct Options {
+    args: Vec<String>,
+    overrides: Params,
+    visualize: bool,
+    fps: Option<f64>,
+    frames_dir: Option<String>,
+    trace_file: Option<String>,
+    size: Option<usize>,
+    seed: Option<u64>,
+    cases: Option<usize>,
+}
+
+impl Options {
+    fn visualizer(&self) -> Result<Option<Box<dyn Visualizer>>, Error> {
+        Ok(match (&self.frames_dir, self.visualize) {
+            (Some(dir), _) => Some(Box::new(FrameDump::new(dir)?)),
+            (None, true) => Some(Box::new(TerminalPlayer::new(
+                self.fps.unwrap_or(visualize::DEFAULT_FPS),
+            ))),
+            (None, false) => None,
+        })
+    }
+}
+
+fn parse_day(arg: Option<&String>) -> u32 {
+    arg.map(|s| s.parse().unwrap_or(1)).unwrap_or(1)
+}
+
+// splits the flags from the positional arguments
+fn parse_args() -> Result<Options, Error> {
+    let mut opts = Options::default();
+
+    let mut it = env::args().skip(1);
+    while let Some(arg) = it.next() {
+        let mut value = |name: &str| it.next().ok_or_else(|| anyhow!("{name} needs a value"));
+
+        match arg.as_str() {
+            "--param" => opts.overrides.set_from_str(&value("--param")?)?,
+            "--visualize" => opts.visualize = true,
+            "--fps" => {
+                opts.visualize = true;
+                opts.fps = Some(value("--fps")?.parse()?);
+            }
+            "--frames-dir" => opts.frames_dir = Some(value("--frames-dir")?),
+            "--trace" => opts.trace_file = Some(value("--trace")?),
+            "--size" => opts.size = Some(value("--size")?.parse()?),
+            "--seed" => opts.seed = Some(value("--seed")?.parse()?),
+            "--cases" => opts.cases = Some(value("--cases")?.parse()?),
+            _ => match arg.strip_prefix("--param=") {
+                Some(p) => opts.overrides.set_from_str(p)?,
+                None => opts.args.push(arg),
+            },
+        }
+    }
+
+    Ok(opts)
+}
+
+fn day_solver(day: u32, overrides: &Params) -> Result<(Box<dyn Runner>, Params), Error> {
+    let s = solver(day).ok_or_else(|| anyhow!("Day {day} hasn't been solved yet :("))?;
+    let params = s.params(overrides)?;
+    Ok((s, params))
+}
+
+fn run(opts: &Options) -> Result<(), Error> {
+    let args = &opts.args;
+
+    match args.first().map(String::as_str) {
+        Some("watch") => {
+            let day = parse_day(args.get(1));
+            day_solver(day, &opts.overrides)?;
+            watch::watch(day, &opts.overrides)?;
+        }
+        Some("serve") => {
+            let addr = args.get(1).map_or(serve::DEFAULT_ADDR, String::as_str);
+            serve::serve(addr)?;
+        }
+        Some("params") => {
+            let day = parse_day(args.get(1));
+            let (_, params) = day_solver(day, &opts.overrides)?;
+            print!("{params}");
+        }
+        Some("repl") => {
+            let day = parse_day(args.get(1));
+            let (s, params) = day_solver(day, &opts.overrides)?;
+            s.repl(day, params)?;
+        }
+        Some("generate") => {
+            let day = parse_day(args.get(1));
+            let size = opts.size.unwrap_or(generate::DEFAULT_SIZE);
+            generate::generate(day, size, opts.seed)?;
+        }
+        Some("difftest") => {
+            let day = parse_day(args.get(1));
+            let (s, params) = day_solver(day, &opts.overrides)?;
+            let options = DiffOptions {
+                cases: opts.cases.unwrap_or(difftest::DEFAULT_CASES),
+                size: opts.size.unwrap_or(difftest::DEFAULT_SIZE),
+                seed: opts.seed.unwrap_or_else(|| Rng::from_time().1),
+            };
+            println!(
+                "Day {day}, size {}, seeds from {}",
+                options.size, options.seed
+            );
+            s.difftest(day, &options, &params)?;
+        }
+        Some("check") => {
+            let day = parse_day(args.get(1));
+            let (s, params) = day_solver(day, &opts.overrides)?;
+            s.check(day, &params);
+        }
+        _ => {
+            let day = parse_day(args.first());
+            let (s, params) = day_solver(day, &opts.overrides)?;
+
+            if let Some(v) = opts.visualizer()? {
+                visualize::install(v);
+            }
+            if opts.trace_file.is_some() {
+                trace::start();
+            }
+
+            let result = s.run(day, &params);
+
+            visualize::finish();
+            if let Some(file) = &opts.trace_file {
+                trace::finish(file)?;
+            }
+            result?;
+        }
+    }
+
+    Ok(())
+}
 
 fn main() {
-    let day = env::args()
-        .nth(1)
-        .unwrap_or_else(|| String::from("1"))
-        .parse()
-        .unwrap_or(1);
-    exec_day(day);
+    if let Err(e) = parse_args().and_then(|opts| run(&opts)) {
+        eprintln!("{e}");
+        process::exit(1);
+    }
 }
@@ -0,0 +1,96 @@
+use crate::solver::{input_file, Params, Parts, Solver};
+use anyhow::{anyhow, Error};
+use std::io;
+use std::io::{BufRead, Write};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+
+const HELP: &str = "\
+1, 2, both          solve part 1, part 2 or both parts
+input               print the parsed input
+params              print the current parameters
+set <key>=<value>   change a parameter
+reset               restore the default parameters
+reload [file]       parse the input file again, or another file
+help                print this help
+quit                leave the REPL";
+
+// parses the input once, then lets the parts be solved over and over
+pub fn repl<S: Solver>(solver: &S, day: u32, params: Params) -> Result<(), Error> {
+    let mut path = PathBuf::from(input_file(day));
+    let mut input = load(solver, &path)?;
+    let mut params = params;
+
+    println!(
+        "Day {day}, input from {}, type help for commands",
+        path.display()
+    );
+
+    let stdin = io::stdin();
+    loop {
+        print!("day{:02}> ", day);
+        io::stdout().flush()?;
+
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line)? == 0 {
+            break;
+        }
+        let line = line.trim();
+        let (cmd, arg) = line.split_once(' ').unwrap_or((line, ""));
+
+        // solvers panic on unexpected input, which shouldn't end the session
+        let result = catch_unwind(AssertUnwindSafe(|| -> Result<bool, Error> {
+            match cmd {
+                "" => {}
+                "1" | "2" | "both" => {
+                    solver.check_input(&input, &params)?;
+                    let parts = match cmd {
+                        "1" => Parts::First,
+                        "2" => Parts::Second,
+                        _ => Parts::Both,
+                    };
+                    solver.solve_parsed(&input, parts, &params).print();
+                }
+                "input" => println!("{input:#?}"),
+                "params" => print!("{params}"),
+                "set" => {
+                    let mut overrides = Params::new();
+                    overrides.set_from_str(arg)?;
+                    let merged = params.merge(&overrides)?;
+                    solver.check_params(&merged)?;
+                    params = merged;
+                }
+                "reset" => params = solver.default_params(),
+                "reload" => {
+                    // a file that fails to load doesn't replace the current one
+                    let new_path = match arg.trim() {
+                        "" => path.clone(),
+                        p => PathBuf::from(p),
+                    };
+                    input = load(solver, &new_path)?;
+                    println!("Reloaded {}", new_path.display());
+                    path = new_path;
+                }
+                "help" => println!("{HELP}"),
+                "quit" | "exit" => return Ok(false),
+                _ => Err(anyhow!("{cmd}: unknown command, type help for commands"))?,
+            }
+            Ok(true)
+        }));
+
+        match result {
+            Ok(Ok(true)) => {}
+            Ok(Ok(false)) => break,
+            Ok(Err(e)) => println!("{e}"),
+            Err(_) => println!("The solver panicked, the input or parameters may be invalid"),
+        }
+    }
+
+    Ok(())
+}
+
+fn load<S: Solver>(solver: &S, path: &Path) -> Result<S::Input, Error> {
+    solver
+        .load_input(path)
+        .map_err(|e| anyhow!("{}: {e}", path.display()))
+}
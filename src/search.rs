@@ -0,0 +1,273 @@
+use crate::grid::Grid;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+
+const NO_PARENT: usize = usize::MAX;
+
+// the states from the start to the goal, both included, and what it cost to get there
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Path<S> {
+    pub cost: u64,
+    pub states: Vec<S>,
+}
+
+// every state seen so far, with the index of the one it was reached from
+struct Visited<S> {
+    states: Vec<S>,
+    index: HashMap<S, usize>,
+    parents: Vec<usize>,
+}
+
+impl<S: Clone + Eq + Hash> Visited<S> {
+    fn new() -> Self {
+        Self {
+            states: vec![],
+            index: HashMap::new(),
+            parents: vec![],
+        }
+    }
+
+    // the index of the state, and whether it's the first time it's seen
+    fn index_of(&mut self, s: S) -> (usize, bool) {
+        if let Some(&i) = self.index.get(&s) {
+            return (i, false);
+        }
+        self.states.push(s.clone());
+        self.parents.push(NO_PARENT);
+        self.index.insert(s, self.states.len() - 1);
+        (self.states.len() - 1, true)
+    }
+
+    fn path(&self, mut i: usize) -> Vec<S> {
+        let mut path = vec![self.states[i].clone()];
+        while self.parents[i] != NO_PARENT {
+            i = self.parents[i];
+            path.push(self.states[i].clone());
+        }
+        path.reverse();
+        path
+    }
+}
+
+// breadth first search, yielding every reachable state with its distance from the closest
+// start, closest first
+pub struct Bfs<S, N> {
+    visited: Visited<S>,
+    queue: VecDeque<(usize, usize)>,
+    neighbours: N,
+}
+
+impl<S, N, I> Bfs<S, N>
+where
+    S: Clone + Eq + Hash,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = S>,
+{
+    pub fn new(start: S, neighbours: N) -> Self {
+        Self::from_starts([start], neighbours)
+    }
+
+    pub fn from_starts(starts: impl IntoIterator<Item = S>, neighbours: N) -> Self {
+        let mut visited = Visited::new();
+        let queue = starts
+            .into_iter()
+            .map(|s| visited.index_of(s))
+            .filter(|&(_, new)| new)
+            .map(|(i, _)| (i, 0))
+            .collect();
+        Self {
+            visited,
+            queue,
+            neighbours,
+        }
+    }
+
+    // the shortest path from one of the starts to a state that was already yielded
+    pub fn path_to(&self, s: &S) -> Option<Vec<S>> {
+        self.visited.index.get(s).map(|&i| self.visited.path(i))
+    }
+}
+
+impl<S, N, I> Iterator for Bfs<S, N>
+where
+    S: Clone + Eq + Hash,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = S>,
+{
+    type Item = (S, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (i, dist) = self.queue.pop_front()?;
+        let state = self.visited.states[i].clone();
+
+        for n in (self.neighbours)(&state) {
+            let (j, new) = self.visited.index_of(n);
+            if new {
+                self.visited.parents[j] = i;
+                self.queue.push_back((j, dist + 1));
+            }
+        }
+
+        Some((state, dist))
+    }
+}
+
+pub fn bfs<S, N, I, G>(start: S, neighbours: N, mut is_goal: G) -> Option<Path<S>>
+where
+    S: Clone + Eq + Hash,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = S>,
+    G: FnMut(&S) -> bool,
+{
+    let mut search = Bfs::new(start, neighbours);
+    while let Some((s, dist)) = search.next() {
+        if is_goal(&s) {
+            return Some(Path {
+                cost: dist as u64,
+                states: search.path_to(&s)?,
+            });
+        }
+    }
+    None
+}
+
+// the distance to every state reachable from the starts
+pub fn distances<S, N, I>(starts: impl IntoIterator<Item = S>, neighbours: N) -> HashMap<S, usize>
+where
+    S: Clone + Eq + Hash,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = S>,
+{
+    Bfs::from_starts(starts, neighbours).collect()
+}
+
+pub fn dijkstra<S, N, I, C, G>(start: S, neighbours: N, cost: C, is_goal: G) -> Option<Path<S>>
+where
+    S: Clone + Eq + Hash,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = S>,
+    C: FnMut(&S, &S) -> u64,
+    G: FnMut(&S) -> bool,
+{
+    astar(start, neighbours, cost, |_| 0, is_goal)
+}
+
+// Dijkstra guided by `heuristic`, which must never overestimate the cost left to reach
+// a goal for the path to be the cheapest
+pub fn astar<S, N, I, C, H, G>(
+    start: S,
+    mut neighbours: N,
+    mut cost: C,
+    mut heuristic: H,
+    mut is_goal: G,
+) -> Option<Path<S>>
+where
+    S: Clone + Eq + Hash,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = S>,
+    C: FnMut(&S, &S) -> u64,
+    H: FnMut(&S) -> u64,
+    G: FnMut(&S) -> bool,
+{
+    let mut visited = Visited::new();
+    let (i, _) = visited.index_of(start.clone());
+    let mut best = vec![0];
+    let mut heap = BinaryHeap::from([Reverse((heuristic(&start), 0, i))]);
+
+    while let Some(Reverse((_, so_far, i))) = heap.pop() {
+        // a cheaper way here was found after this one was queued
+        if so_far > best[i] {
+            continue;
+        }
+        let state = visited.states[i].clone();
+        if is_goal(&state) {
+            return Some(Path {
+                cost: so_far,
+                states: visited.path(i),
+            });
+        }
+
+        for n in neighbours(&state) {
+            let c = so_far + cost(&state, &n);
+            let estimate = c + heuristic(&n);
+            let (j, new) = visited.index_of(n);
+            if new {
+                best.push(c);
+            } else if c >= best[j] {
+                continue;
+            }
+            best[j] = c;
+            visited.parents[j] = i;
+            heap.push(Reverse((estimate, c, j)));
+        }
+    }
+
+    None
+}
+
+// the neighbours of a grid coordinate that can be moved to from it, for the searches
+pub fn grid_moves4<'a, T>(
+    grid: &'a Grid<T>,
+    c: (usize, usize),
+    can_move: impl Fn(&T, &T) -> bool + 'a,
+) -> impl Iterator<Item = (usize, usize)> + 'a {
+    let from = grid.get(c);
+    grid.neighbours_coords4(c)
+        .filter(move |&n| match (from, grid.get(n)) {
+            (Some(from), Some(to)) => can_move(from, to),
+            _ => false,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_searches() {
+        let grid: Grid<u8> = "S.#.\n..#.\n#...\n..#E\n".parse().unwrap();
+        let moves = || |&c: &(usize, usize)| grid_moves4(&grid, c, |_, &to| to != b'#');
+        let exit = |c: &(usize, usize)| grid.get(c) == Some(&b'E');
+
+        let path = bfs((0, 0), moves(), exit).unwrap();
+        assert_eq!(path.cost, 6);
+        assert_eq!(path.states.len(), 7);
+        assert_eq!(path.states.first(), Some(&(0, 0)));
+        assert!(path
+            .states
+            .windows(2)
+            .all(|w| grid.neighbours_coords4(w[0]).any(|n| n == w[1])));
+
+        let manhattan = |&(x, y): &(usize, usize)| (3 - x + 3 - y) as u64;
+        let by_astar = astar((0, 0), moves(), |_, _| 1, manhattan, exit).unwrap();
+        assert_eq!(by_astar.cost, 6);
+
+        assert!(bfs((0, 0), moves(), |&c| c == (3, 0)).is_some());
+        assert!(bfs((0, 0), moves(), |&c| c == (2, 0)).is_none());
+
+        let dist = distances([(0, 0), (3, 3)], moves());
+        assert_eq!(dist[&(3, 3)], 0);
+        assert_eq!(dist[&(3, 0)], 3);
+        assert!(!dist.contains_key(&(2, 0)));
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        // the direct edge is more expensive than the detour
+        let edges = HashMap::from([("a", vec![("b", 1), ("c", 5)]), ("b", vec![("c", 1)])]);
+        let neighbours = |s: &&str| {
+            edges
+                .get(s)
+                .into_iter()
+                .flatten()
+                .map(|&(n, _)| n)
+                .collect::<Vec<_>>()
+        };
+        let cost = |a: &&str, b: &&str| edges[a].iter().find(|(n, _)| n == b).unwrap().1;
+
+        let path = dijkstra("a", neighbours, cost, |&s| s == "c").unwrap();
+        assert_eq!(path.cost, 2);
+        assert_eq!(path.states, vec!["a", "b", "c"]);
+    }
+}
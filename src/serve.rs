@@ -0,0 +1,251 @@
+use crate::solutions::{solver, DAYS};
+use crate::solver::{Parts, Report};
+use std::fmt::Write as FmtWrite;
+use std::io;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::thread;
+use std::time::Duration;
+
+pub const DEFAULT_ADDR: &str = "127.0.0.1:8022";
+
+// inputs are a few dozen kilobytes, anything much bigger is a mistake
+const MAX_BODY_SIZE: usize = 16 * 1024 * 1024;
+
+struct Request {
+    method: String,
+    path: String,
+    body: Vec<u8>,
+}
+
+struct Response {
+    status: u16,
+    body: String,
+}
+
+impl Response {
+    fn ok(body: String) -> Self {
+        Self { status: 200, body }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            body: format!("{{\"error\":{}}}", json_string(message)),
+        }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            422 => "Unprocessable Entity",
+            _ => "Internal Server Error",
+        }
+    }
+}
+
+pub fn serve(addr: &str) -> io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    println!("Listening on http://{}", listener.local_addr()?);
+
+    for stream in listener.incoming().flatten() {
+        thread::spawn(move || {
+            if let Err(e) = handle_connection(stream) {
+                eprintln!("connection error: {e}");
+            }
+        });
+    }
+
+    Ok(())
+}
+
+fn handle_connection(mut stream: TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(30)))?;
+
+    let response = match read_request(&stream) {
+        Ok(req) => route(&req),
+        Err(e) if e.kind() == io::ErrorKind::InvalidData => Response::error(400, &e.to_string()),
+        Err(e) => return Err(e),
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        response.reason(),
+        response.body.len(),
+        response.body
+    )?;
+    stream.flush()
+}
+
+fn read_request(stream: &TcpStream) -> io::Result<Request> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg);
+    let mut reader = BufReader::new(stream);
+
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut fields = line.split_whitespace();
+    let method = fields.next().ok_or_else(|| invalid("missing method"))?;
+    let path = fields.next().ok_or_else(|| invalid("missing path"))?;
+    let mut req = Request {
+        method: method.to_string(),
+        path: path.to_string(),
+        body: vec![],
+    };
+
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim_end().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| invalid("invalid content length"))?;
+            }
+        }
+    }
+
+    if content_length > MAX_BODY_SIZE {
+        return Err(invalid("body too large"));
+    }
+
+    req.body.resize(content_length, 0);
+    reader.read_exact(&mut req.body)?;
+
+    Ok(req)
+}
+
+fn route(req: &Request) -> Response {
+    let segments = req
+        .path
+        .trim_matches('/')
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>();
+
+    match (req.method.as_str(), segments.as_slice()) {
+        ("GET", ["days"]) => list_days(),
+        ("POST", ["days", day]) => solve(day, None, &req.body),
+        ("POST", ["days", day, part]) => solve(day, Some(part), &req.body),
+        (_, ["days", ..]) => Response::error(405, "method not allowed"),
+        _ => Response::error(404, "not found"),
+    }
+}
+
+fn list_days() -> Response {
+    let days = DAYS.iter().map(|d| d.to_string()).collect::<Vec<_>>();
+    Response::ok(format!("{{\"days\":[{}]}}", days.join(",")))
+}
+
+fn solve(day: &str, part: Option<&str>, body: &[u8]) -> Response {
+    let day = match day.parse::<u32>() {
+        Ok(d) => d,
+        Err(_) => return Response::error(400, "invalid day"),
+    };
+    let parts = match part {
+        None => Parts::Both,
+        Some("1") => Parts::First,
+        Some("2") => Parts::Second,
+        Some(_) => return Response::error(404, "part must be 1 or 2"),
+    };
+    let s = match solver(day) {
+        Some(s) => s,
+        None => return Response::error(404, "day not solved"),
+    };
+
+    // a solver panicking shouldn't drop the connection either
+    let params = s.default_params();
+    match catch_unwind(AssertUnwindSafe(|| s.solve_bytes(body, parts, &params))) {
+        Ok(Ok(report)) => Response::ok(report_json(day, &report)),
+        Ok(Err(e)) => Response::error(422, &format!("invalid input: {e}")),
+        Err(_) => Response::error(422, "unable to solve input"),
+    }
+}
+
+fn report_json(day: u32, report: &Report) -> String {
+    let mut s = format!(
+        "{{\"day\":{},\"parse_ms\":{},",
+        day,
+        report.parse_time.as_secs_f64() * 1000.0
+    );
+    if let Some(time) = report.shared_time {
+        let _ = write!(s, "\"shared_ms\":{},", time.as_secs_f64() * 1000.0);
+    }
+    s.push_str("\"answers\":[");
+
+    for (i, a) in report.answers.iter().enumerate() {
+        if i > 0 {
+            s.push(',');
+        }
+        let _ = write!(
+            s,
+            "{{\"part\":{},\"answer\":{},\"ms\":{}}}",
+            a.part,
+            json_string(&a.value),
+            a.time.as_secs_f64() * 1000.0
+        );
+    }
+
+    s.push_str("]}");
+    s
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_string() {
+        assert_eq!(json_string("abc"), "\"abc\"");
+        assert_eq!(json_string("a\"b\\c\nd"), "\"a\\\"b\\\\c\\nd\"");
+        assert_eq!(json_string("\u{1}"), "\"\\u0001\"");
+    }
+
+    #[test]
+    fn test_route() {
+        let req = Request {
+            method: "POST".to_string(),
+            path: "/days/4/1".to_string(),
+            body: b"2-4,6-8\n2-8,3-7\n".to_vec(),
+        };
+        let res = route(&req);
+        assert_eq!(res.status, 200);
+        assert!(res.body.contains("\"answer\":\"1\""));
+
+        let req = Request {
+            method: "GET".to_string(),
+            path: "/nope".to_string(),
+            body: vec![],
+        };
+        assert_eq!(route(&req).status, 404);
+    }
+}
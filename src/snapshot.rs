@@ -0,0 +1,89 @@
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+
+// set to anything but 0 to write the renders as the new snapshots
+pub const UPDATE_VAR: &str = "UPDATE_SNAPSHOTS";
+
+pub fn snapshot_file(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(format!("{name}.txt"))
+}
+
+fn updating() -> bool {
+    env::var(UPDATE_VAR).is_ok_and(|v| !v.is_empty() && v != "0")
+}
+
+// compares a multi-line render with `tests/snapshots/<name>.txt`, panicking with a line
+// diff when they differ, or replaces the snapshot when `UPDATE_SNAPSHOTS` is set
+#[track_caller]
+pub fn assert_snapshot(name: &str, actual: &str) {
+    let path = snapshot_file(name);
+
+    if updating() {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).expect("unable to create the snapshot directory");
+        }
+        fs::write(&path, actual).expect("unable to write the snapshot");
+        return;
+    }
+
+    let expected = match fs::read_to_string(&path) {
+        Ok(s) => s,
+        Err(_) => panic!(
+            "no snapshot at {}, run with {UPDATE_VAR}=1 to create it",
+            path.display()
+        ),
+    };
+    if expected != actual {
+        panic!(
+            "{} doesn't match (- snapshot, + actual), run with {UPDATE_VAR}=1 to accept:\n{}",
+            path.display(),
+            line_diff(&expected, actual)
+        );
+    }
+}
+
+// compares line by line, which suits renders that keep their shape
+pub fn line_diff(old: &str, new: &str) -> String {
+    let old = old.split('\n').collect::<Vec<_>>();
+    let new = new.split('\n').collect::<Vec<_>>();
+    let mut out = String::new();
+
+    for i in 0..old.len().max(new.len()) {
+        let _ = match (old.get(i), new.get(i)) {
+            (Some(o), Some(n)) if o == n => writeln!(out, "  {n}"),
+            (o, n) => {
+                if let Some(o) = o {
+                    let _ = writeln!(out, "- {}", visible_end(o));
+                }
+                match n {
+                    Some(n) => writeln!(out, "+ {}", visible_end(n)),
+                    None => Ok(()),
+                }
+            }
+        };
+    }
+
+    out
+}
+
+// trailing spaces are the usual invisible difference
+fn visible_end(line: &str) -> String {
+    let trimmed = line.trim_end_matches(' ');
+    let spaces = line.len() - trimmed.len();
+    format!("{trimmed}{}", "·".repeat(spaces))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_diff() {
+        assert_eq!(line_diff("a\nb", "a\nc"), "  a\n- b\n+ c\n");
+        assert_eq!(line_diff("a", "a\nb "), "  a\n+ b·\n");
+    }
+}
@@ -1,6 +1,7 @@
+use crate::generate::Rng;
 use crate::solver::{ReadExt, Solver};
+use anyhow::Error;
 use itertools::Itertools;
-use std::convert::Infallible;
 use std::io::Read;
 use std::str::FromStr;
 
@@ -11,8 +12,8 @@ impl Solver for Problem {
     type Output1 = u64;
     type Output2 = u64;
 
-    fn parse_input<R: Read>(&self, r: R) -> Self::Input {
-        r.split_groups()
+    fn parse_input<R: Read>(&self, r: R) -> Result<Self::Input, Error> {
+        r.try_split_groups()
     }
 
     fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
@@ -27,15 +28,30 @@ impl Solver for Problem {
             .take(3)
             .sum()
     }
+
+    // `size` elves carrying a few snacks each
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        let elves = (0..size.max(1))
+            .map(|_| {
+                (0..rng.range(1..8))
+                    .map(|_| rng.range(1000..60000).to_string())
+                    .join("\n")
+            })
+            .collect::<Vec<_>>();
+        Some(elves.join("\n\n") + "\n")
+    }
 }
 
+#[derive(Debug)]
 pub struct Calories(Vec<u64>);
 
 impl FromStr for Calories {
-    type Err = Infallible;
+    type Err = Error;
 
+    // u32 each, so that the sums can't overflow
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Self(s.as_bytes().split_lines()))
+        let calories: Vec<u32> = s.as_bytes().try_split_lines()?;
+        Ok(Self(calories.into_iter().map(u64::from).collect()))
     }
 }
 
@@ -1,4 +1,6 @@
-use crate::solver::{ReadExt, Solver};
+use crate::generate::Rng;
+use crate::solver::{Cursor, ParseError, ReadExt, Solver};
+use anyhow::Error;
 use std::cmp::Ordering;
 use std::io::Read;
 use std::str::FromStr;
@@ -10,8 +12,8 @@ impl Solver for Problem {
     type Output1 = u32;
     type Output2 = u32;
 
-    fn parse_input<R: Read>(&self, r: R) -> Self::Input {
-        r.split_lines()
+    fn parse_input<R: Read>(&self, r: R) -> Result<Self::Input, Error> {
+        r.try_split_lines()
     }
 
     fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
@@ -21,9 +23,24 @@ impl Solver for Problem {
     fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
         input.iter().map(|r| r.score2()).sum()
     }
+
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(
+            (0..size.max(1))
+                .map(|_| {
+                    format!(
+                        "{} {}\n",
+                        rng.pick(&["A", "B", "C"]),
+                        rng.pick(&["X", "Y", "Z"])
+                    )
+                })
+                .collect(),
+        )
+    }
 }
 
-#[derive(Clone, Eq, PartialEq)]
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub enum RPS {
     Rock,
     Paper,
@@ -50,19 +67,7 @@ impl Ord for RPS {
     }
 }
 
-impl FromStr for RPS {
-    type Err = &'static str;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "A" => Ok(Self::Rock),
-            "B" => Ok(Self::Paper),
-            "C" => Ok(Self::Scissors),
-            _ => Err("invalid input"),
-        }
-    }
-}
-
+#[derive(Clone, Debug)]
 pub enum Intent {
     Lose,
     Draw,
@@ -79,19 +84,7 @@ impl Intent {
     }
 }
 
-impl FromStr for Intent {
-    type Err = &'static str;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "X" => Ok(Self::Lose),
-            "Y" => Ok(Self::Draw),
-            "Z" => Ok(Self::Win),
-            _ => Err("invalid input"),
-        }
-    }
-}
-
+#[derive(Debug)]
 pub struct Round {
     opponent: RPS,
     player: Intent,
@@ -131,13 +124,21 @@ impl Round {
 }
 
 impl FromStr for Round {
-    type Err = &'static str;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut spl = s.split(' ');
-        Ok(Self {
-            opponent: spl.next().ok_or("missing field")?.parse()?,
-            player: spl.next().ok_or("missing field")?.parse()?,
+        Cursor::parse(s, |c| {
+            let opponent =
+                c.one_of(&[("A", RPS::Rock), ("B", RPS::Paper), ("C", RPS::Scissors)])?;
+            c.literal(" ")?;
+            Ok(Self {
+                opponent,
+                player: c.one_of(&[
+                    ("X", Intent::Lose),
+                    ("Y", Intent::Draw),
+                    ("Z", Intent::Win),
+                ])?,
+            })
         })
     }
 }
@@ -1,6 +1,7 @@
+use crate::generate::Rng;
 use crate::solver::{ReadExt, Solver};
+use anyhow::Error;
 use std::collections::BTreeSet;
-use std::convert::Infallible;
 use std::io::Read;
 use std::str::FromStr;
 
@@ -11,8 +12,8 @@ impl Solver for Problem {
     type Output1 = u64;
     type Output2 = u64;
 
-    fn parse_input<R: Read>(&self, r: R) -> Self::Input {
-        r.split_lines()
+    fn parse_input<R: Read>(&self, r: R) -> Result<Self::Input, Error> {
+        r.try_split_lines()
     }
 
     fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
@@ -30,6 +31,38 @@ impl Solver for Problem {
             .map(|i| i.priority())
             .sum()
     }
+
+    // groups of three rucksacks sharing exactly one badge, each with exactly one item
+    // in both compartments
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        let mut out = String::new();
+
+        for _ in 0..((size + 2) / 3).max(1) {
+            let mut letters = (b'a'..=b'z').chain(b'A'..=b'Z').collect::<Vec<_>>();
+            rng.shuffle(&mut letters);
+            let (badge, rest) = letters.split_first()?;
+
+            // each rucksack draws from its own 17 letters, so that only the badge is common
+            for pool in rest.chunks(17) {
+                let (&shared, pool) = pool.split_first()?;
+                let (left_pool, right_pool) = pool.split_at(pool.len() / 2);
+                let n = rng.range(2..16) as usize;
+
+                let mut left = vec![shared, *badge];
+                left.extend((2..n).map(|_| *rng.pick(left_pool)));
+                let mut right = vec![shared];
+                right.extend((1..n).map(|_| *rng.pick(right_pool)));
+                rng.shuffle(&mut left);
+                rng.shuffle(&mut right);
+
+                left.extend(right);
+                out.push_str(&String::from_utf8(left).ok()?);
+                out.push('\n');
+            }
+        }
+
+        Some(out)
+    }
 }
 
 fn groups(sacks: &[RuckSack]) -> Vec<&[RuckSack]> {
@@ -50,6 +83,7 @@ fn find_common_in_group(group: &[RuckSack]) -> Option<Item> {
     dup_fst.next().cloned().cloned()
 }
 
+#[derive(Debug)]
 pub struct RuckSack(Vec<Item>);
 
 impl RuckSack {
@@ -66,14 +100,17 @@ impl RuckSack {
 }
 
 impl FromStr for RuckSack {
-    type Err = Infallible;
+    type Err = &'static str;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() % 2 != 0 {
+            return Err("compartments of different sizes");
+        }
         Ok(Self(s.as_bytes().iter().map(|b| Item(*b)).collect()))
     }
 }
 
-#[derive(Clone, Eq, PartialEq, Ord, PartialOrd)]
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
 pub struct Item(u8);
 
 impl Item {
@@ -105,7 +142,7 @@ wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn
 ttgJtRGJQctTZtZT
 CrZsJsPPZsGzwwsLwLmpwMDw";
         let pb = Problem {};
-        let input = pb.parse_input(input.as_bytes());
+        let input = pb.parse_input(input.as_bytes()).unwrap();
         assert_eq!(pb.solve_first(&input), 157);
         assert_eq!(pb.solve_second(&input), 70);
     }
@@ -1,6 +1,6 @@
-use crate::solver::{ReadExt, Solver};
-use scan_fmt::scan_fmt;
-use std::collections::BTreeSet;
+use crate::generate::Rng;
+use crate::solver::{Cursor, ParseError, ReadExt, Solver};
+use anyhow::Error;
 use std::io::Read;
 use std::ops::RangeInclusive;
 use std::str::FromStr;
@@ -12,8 +12,8 @@ impl Solver for Problem {
     type Output1 = usize;
     type Output2 = usize;
 
-    fn parse_input<R: Read>(&self, r: R) -> Self::Input {
-        r.split_lines()
+    fn parse_input<R: Read>(&self, r: R) -> Result<Self::Input, Error> {
+        r.try_split_lines()
     }
 
     fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
@@ -23,12 +23,27 @@ impl Solver for Problem {
     fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
         input.iter().filter(|p| p.overlaps()).count()
     }
+
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        let mut range = || {
+            let a = rng.range(1..100);
+            (a, rng.range(a..100))
+        };
+        Some(
+            (0..size.max(1))
+                .map(|_| {
+                    let ((a, b), (c, d)) = (range(), range());
+                    format!("{a}-{b},{c}-{d}\n")
+                })
+                .collect(),
+        )
+    }
 }
 
 #[derive(Debug)]
 pub struct Pair {
-    first: RangeInclusive<u8>,
-    second: RangeInclusive<u8>,
+    first: RangeInclusive<u32>,
+    second: RangeInclusive<u32>,
 }
 
 impl Pair {
@@ -38,20 +53,32 @@ impl Pair {
     }
 
     fn overlaps(&self) -> bool {
-        let f = BTreeSet::from_iter(self.first.clone());
-        let s = BTreeSet::from_iter(self.second.clone());
-        f.intersection(&s).count() > 0
+        self.first.start() <= self.second.end() && self.second.start() <= self.first.end()
+    }
+}
+
+fn range(c: &mut Cursor) -> Result<RangeInclusive<u32>, ParseError> {
+    let start = c.number()?;
+    c.literal("-")?;
+    let before_end = c.clone();
+    let end = c.number()?;
+    if end < start {
+        return Err(before_end.error(format!("a section of at least {start}")));
     }
+    Ok(start..=end)
 }
 
 impl FromStr for Pair {
-    type Err = &'static str;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (a, b, c, d) = scan_fmt!(s, "{}-{},{}-{}", u8, u8, u8, u8).map_err(|_| "error")?;
-        Ok(Self {
-            first: a..=b,
-            second: c..=d,
+        Cursor::parse(s, |c| {
+            let first = range(c)?;
+            c.literal(",")?;
+            Ok(Self {
+                first,
+                second: range(c)?,
+            })
         })
     }
 }
@@ -111,7 +138,7 @@ mod tests {
 6-6,4-6
 2-6,4-8";
         let pb = Problem {};
-        let input = pb.parse_input(input.as_bytes());
+        let input = pb.parse_input(input.as_bytes()).unwrap();
 
         assert_eq!(pb.solve_first(&input), 2);
         assert_eq!(pb.solve_second(&input), 4);
@@ -1,7 +1,10 @@
-use crate::solver::{ReadExt, Solver};
+use crate::generate::Rng;
+use crate::solver::{Cursor, Params, ParseError, ReadExt, Solver};
+use crate::visualize;
 use anyhow::{anyhow, Error};
-use scan_fmt::scan_fmt;
+use itertools::Itertools;
 use std::collections::VecDeque;
+use std::fmt::{Display, Formatter};
 use std::io::Read;
 use std::str::FromStr;
 
@@ -12,36 +15,92 @@ impl Solver for Problem {
     type Output1 = String;
     type Output2 = String;
 
-    fn parse_input<R: Read>(&self, r: R) -> Self::Input {
-        Supply::from_reader(r).expect("correct supply")
+    fn parse_input<R: Read>(&self, r: R) -> Result<Self::Input, Error> {
+        Supply::from_reader(r)
     }
 
     fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
         let mut crates = input.crates.clone();
         for i in &input.instructions {
             crates.move_crates(i);
+            visualize::emit(|| crates.to_string());
         }
 
-        crates
-            .0
-            .iter()
-            .filter_map(|c| c.last())
-            .map(|b| *b as char)
-            .collect::<String>()
+        crates.tops()
     }
 
     fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
         let mut crates = input.crates.clone();
         for i in &input.instructions {
             crates.move_crates_advanced(i);
+            visualize::emit(|| crates.to_string());
         }
 
-        crates
-            .0
-            .iter()
-            .filter_map(|c| c.last())
-            .map(|b| *b as char)
-            .collect::<String>()
+        crates.tops()
+    }
+
+    // moving crates one at a time is moving a single crate as many times
+    fn reference_first(&self, input: &Self::Input, _params: &Params) -> Option<Self::Output1> {
+        let mut crates = input.crates.clone();
+        for i in &input.instructions {
+            for _ in 0..i.n {
+                crates.move_crates_advanced(&Instruction { n: 1, ..*i });
+            }
+        }
+        Some(crates.tops())
+    }
+
+    // moving several crates at once is moving them to the side, then on the other stack
+    fn reference_second(&self, input: &Self::Input, _params: &Params) -> Option<Self::Output2> {
+        let mut crates = input.crates.clone();
+        for i in &input.instructions {
+            let mut held = vec![];
+            for _ in 0..i.n {
+                held.push(crates.0[i.from - 1].pop().expect("non empty vector"));
+            }
+            while let Some(c) = held.pop() {
+                crates.0[i.to - 1].push(c);
+            }
+        }
+        Some(crates.tops())
+    }
+
+    // up to nine stacks and `size` moves, each taking crates from a stack that has them
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        let n = rng.range(3..10) as usize;
+        let mut crates = Crates(
+            (0..n)
+                .map(|_| {
+                    (0..rng.range(1..9))
+                        .map(|_| rng.range(b'A' as u64..b'Z' as u64 + 1) as u8)
+                        .collect()
+                })
+                .collect(),
+        );
+
+        let mut out = format!("{crates}\n");
+        for _ in 0..size.max(1) {
+            let from = loop {
+                let i = rng.below(n);
+                if !crates.0[i].is_empty() {
+                    break i;
+                }
+            };
+            let to = (from + 1 + rng.below(n - 1)) % n;
+            let max = crates.0[from].len().min(8);
+            let instr = Instruction {
+                n: rng.range(1..max as u64 + 1) as usize,
+                from: from + 1,
+                to: to + 1,
+            };
+            out.push_str(&format!(
+                "move {} from {} to {}\n",
+                instr.n, instr.from, instr.to
+            ));
+            crates.move_crates(&instr);
+        }
+
+        Some(out)
     }
 }
 
@@ -54,14 +113,38 @@ pub struct Supply {
 impl Supply {
     fn from_reader<R: Read>(r: R) -> Result<Self, Error> {
         let s: Vec<String> = r.split_groups();
-        Ok(Self {
-            crates: s.get(0).ok_or_else(|| anyhow!("missing crates"))?.parse()?,
+        let supply = Self {
+            crates: s
+                .first()
+                .ok_or_else(|| anyhow!("missing crates"))?
+                .parse()?,
             instructions: s
                 .get(1)
                 .ok_or_else(|| anyhow!("missing instructions"))?
                 .as_bytes()
-                .split_lines(),
-        })
+                .try_split_lines()?,
+        };
+        supply.check_moves()?;
+        Ok(supply)
+    }
+
+    // the crane can only take crates that are there
+    fn check_moves(&self) -> Result<(), Error> {
+        let mut heights = self.crates.0.iter().map(Vec::len).collect::<Vec<_>>();
+
+        for (i, instr) in self.instructions.iter().enumerate() {
+            let stack = |n: usize| n.checked_sub(1).filter(|&s| s < heights.len());
+            let (from, to) = stack(instr.from)
+                .zip(stack(instr.to))
+                .ok_or_else(|| anyhow!("move {}: no such stack", i + 1))?;
+            if heights[from] < instr.n {
+                return Err(anyhow!("move {}: not enough crates", i + 1));
+            }
+            heights[from] -= instr.n;
+            heights[to] += instr.n;
+        }
+
+        Ok(())
     }
 }
 
@@ -69,6 +152,14 @@ impl Supply {
 pub struct Crates(Vec<Vec<u8>>);
 
 impl Crates {
+    fn tops(&self) -> String {
+        self.0
+            .iter()
+            .filter_map(|c| c.last())
+            .map(|b| *b as char)
+            .collect()
+    }
+
     fn move_crates(&mut self, instr: &Instruction) {
         let (from, to) = (instr.from - 1, instr.to - 1);
         for _ in 0..instr.n {
@@ -85,6 +176,31 @@ impl Crates {
     }
 }
 
+// draws the stacks the way the puzzle input does
+impl Display for Crates {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let height = self.0.iter().map(|s| s.len()).max().unwrap_or(0);
+
+        for level in (0..height).rev() {
+            let row = self
+                .0
+                .iter()
+                .map(|s| match s.get(level) {
+                    Some(&c) => format!("[{}]", c as char),
+                    None => "   ".to_string(),
+                })
+                .join(" ");
+            writeln!(f, "{}", row.trim_end())?;
+        }
+
+        writeln!(
+            f,
+            "{}",
+            (1..=self.0.len()).map(|i| format!(" {i} ")).join(" ")
+        )
+    }
+}
+
 impl FromStr for Crates {
     type Err = Error;
 
@@ -120,7 +236,7 @@ impl FromStr for Crates {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug)]
 pub struct Instruction {
     n: usize,
     from: usize,
@@ -128,17 +244,28 @@ pub struct Instruction {
 }
 
 impl FromStr for Instruction {
-    type Err = Error;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (n, from, to) = scan_fmt!(s, "move {} from {} to {}", usize, usize, usize)?;
-        Ok(Self { n, from, to })
+        Cursor::parse(s, |c| {
+            c.literal("move ")?;
+            let n = c.number()?;
+            c.literal(" from ")?;
+            let from = c.number()?;
+            c.literal(" to ")?;
+            Ok(Self {
+                n,
+                from,
+                to: c.number()?,
+            })
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::snapshot::assert_snapshot;
 
     #[test]
     fn test_problem() {
@@ -152,9 +279,33 @@ move 3 from 1 to 3
 move 2 from 2 to 1
 move 1 from 1 to 2";
         let pb = Problem {};
-        let input = pb.parse_input(input.as_bytes());
+        let input = pb.parse_input(input.as_bytes()).unwrap();
 
         assert_eq!(pb.solve_first(&input), "CMZ".to_string());
         assert_eq!(pb.solve_second(&input), "MCD".to_string());
+
+        let mut crates = input.crates.clone();
+        for i in &input.instructions {
+            crates.move_crates_advanced(i);
+        }
+        assert_snapshot("day05_crates", &crates.to_string());
+    }
+
+    #[test]
+    fn test_illegal_moves() {
+        let pb = Problem {};
+        let crates = "[Z] [M]\n 1   2 \n\n";
+        assert!(pb
+            .parse_input(format!("{crates}move 1 from 1 to 2\n").as_bytes())
+            .is_ok());
+        assert!(pb
+            .parse_input(format!("{crates}move 2 from 1 to 2\n").as_bytes())
+            .is_err());
+        assert!(pb
+            .parse_input(format!("{crates}move 1 from 3 to 2\n").as_bytes())
+            .is_err());
+        assert!(pb
+            .parse_input(format!("{crates}move 1 from 0 to 2\n").as_bytes())
+            .is_err());
     }
 }
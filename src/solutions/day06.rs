@@ -1,4 +1,6 @@
-use crate::solver::Solver;
+use crate::generate::Rng;
+use crate::solver::{Params, Solver};
+use anyhow::Error;
 use std::collections::BTreeSet;
 use std::io::Read;
 
@@ -9,26 +11,82 @@ impl Solver for Problem {
     type Output1 = usize;
     type Output2 = usize;
 
-    fn parse_input<R: Read>(&self, r: R) -> Self::Input {
-        r.bytes().flatten().collect::<Vec<_>>()
+    // every byte counts when looking for markers
+    const RAW_INPUT: bool = true;
+
+    fn parse_input<R: Read>(&self, mut r: R) -> Result<Self::Input, Error> {
+        let mut buf = vec![];
+        r.read_to_end(&mut buf)?;
+        Ok(buf)
     }
 
     fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
-        solve_for_size(input, 4)
+        self.solve_first_with(input, &self.default_params())
     }
 
     fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
-        solve_for_size(input, 14)
+        self.solve_second_with(input, &self.default_params())
+    }
+
+    fn default_params(&self) -> Params {
+        Params::new()
+            .with("packet_marker_size", 4)
+            .with("message_marker_size", 14)
+    }
+
+    fn check_params(&self, params: &Params) -> Result<(), Error> {
+        params.at_least::<usize>("packet_marker_size", 1)?;
+        params.at_least::<usize>("message_marker_size", 1)?;
+        Ok(())
+    }
+
+    fn solve_first_with(&self, input: &Self::Input, params: &Params) -> Self::Output1 {
+        solve_for_size(input, params.get("packet_marker_size"))
+    }
+
+    fn solve_second_with(&self, input: &Self::Input, params: &Params) -> Self::Output2 {
+        solve_for_size(input, params.get("message_marker_size"))
+    }
+
+    fn reference_first(&self, input: &Self::Input, params: &Params) -> Option<Self::Output1> {
+        Some(reference_marker(input, params.get("packet_marker_size")))
+    }
+
+    fn reference_second(&self, input: &Self::Input, params: &Params) -> Option<Self::Output2> {
+        Some(reference_marker(input, params.get("message_marker_size")))
+    }
+
+    // `size` letters from a small alphabet, ending with the first run of 14 different ones
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        let mut letters = (b'a'..=b'z').collect::<Vec<_>>();
+        let mut out = (0..size)
+            .map(|_| rng.range(b'a' as u64..b'm' as u64) as u8 as char)
+            .collect::<String>();
+
+        rng.shuffle(&mut letters);
+        out.extend(letters[..14].iter().map(|&b| b as char));
+        out.push('\n');
+        Some(out)
     }
 }
 
+// 0 when there's no marker
 fn solve_for_size(input: &[u8], n: usize) -> usize {
     input
         .windows(n)
         .enumerate()
         .find(|&(_, b)| are_all_different(b))
-        .map(|(i, _)| i + n)
-        .expect("not empty")
+        .map_or(0, |(i, _)| i + n)
+}
+
+// compares every pair of bytes before each position
+fn reference_marker(input: &[u8], n: usize) -> usize {
+    (n..=input.len())
+        .find(|&end| {
+            let w = &input[end - n..end];
+            (0..n).all(|i| (i + 1..n).all(|j| w[i] != w[j]))
+        })
+        .unwrap_or(0)
 }
 
 fn are_all_different(bytes: &[u8]) -> bool {
@@ -52,7 +110,7 @@ mod tests {
         let pb = Problem {};
 
         for (input, s1) in *inputs {
-            let input = pb.parse_input(input.as_bytes());
+            let input = pb.parse_input(input.as_bytes()).unwrap();
 
             assert_eq!(pb.solve_first(&input), s1);
         }
@@ -71,7 +129,7 @@ mod tests {
         let pb = Problem {};
 
         for (input, s2) in *inputs {
-            let input = pb.parse_input(input.as_bytes());
+            let input = pb.parse_input(input.as_bytes()).unwrap();
             assert_eq!(pb.solve_second(&input), s2);
         }
     }
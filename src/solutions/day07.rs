@@ -1,7 +1,9 @@
-use crate::solver::Solver;
-use scan_fmt::scan_fmt;
+use crate::generate::Rng;
+use crate::solver::{Cursor, Params, ParseError, Phase, ReadExt, Solver, Stopwatch};
+use anyhow::{anyhow, Error};
 use std::collections::HashMap;
-use std::io::{BufRead, BufReader, Read};
+use std::io::Read;
+use std::str::FromStr;
 
 pub struct Problem;
 
@@ -10,29 +12,172 @@ impl Solver for Problem {
     type Output1 = u64;
     type Output2 = u64;
 
-    fn parse_input<R: Read>(&self, r: R) -> Self::Input {
+    fn parse_input<R: Read>(&self, r: R) -> Result<Self::Input, Error> {
         FileSystem::from_reader(r)
     }
 
     fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
-        (0..input.nodes.len())
-            .filter(|&n| input.is_dir(n))
-            .map(|n| input.node_size(n))
-            .filter(|&s| s <= 100000)
-            .sum()
+        self.solve_first_with(input, &self.default_params())
     }
 
     fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
-        let used_space = input.node_size(0);
-        let unused_space = 70000000 - used_space;
-        let min_needed_cleanup = 30000000 - unused_space;
+        self.solve_second_with(input, &self.default_params())
+    }
+
+    fn default_params(&self) -> Params {
+        Params::new()
+            .with("max_dir_size", 100000)
+            .with("disk_size", 70000000)
+            .with("needed_space", 30000000)
+    }
+
+    fn check_params(&self, params: &Params) -> Result<(), Error> {
+        for key in ["max_dir_size", "disk_size", "needed_space"] {
+            params.try_get::<u64>(key)?;
+        }
+        Ok(())
+    }
+
+    fn solve_first_with(&self, input: &Self::Input, params: &Params) -> Self::Output1 {
+        small_dirs_total(&input.dir_sizes(), params)
+    }
 
-        (0..input.nodes.len())
-            .filter(|&n| input.is_dir(n))
-            .map(|n| input.node_size(n))
-            .filter(|&s| s >= min_needed_cleanup)
-            .min()
-            .unwrap_or_default()
+    fn solve_second_with(&self, input: &Self::Input, params: &Params) -> Self::Output2 {
+        smallest_dir_to_delete(&input.dir_sizes(), params)
+    }
+
+    fn solve_both(
+        &self,
+        input: &Self::Input,
+        params: &Params,
+        sw: &mut Stopwatch,
+    ) -> (Self::Output1, Self::Output2) {
+        let dir_sizes = input.dir_sizes();
+        sw.lap(Phase::Shared);
+        let s1 = small_dirs_total(&dir_sizes, params);
+        sw.lap(Phase::First);
+        let s2 = smallest_dir_to_delete(&dir_sizes, params);
+        sw.lap(Phase::Second);
+        (s1, s2)
+    }
+
+    fn reference_first(&self, input: &Self::Input, params: &Params) -> Option<Self::Output1> {
+        Some(small_dirs_total(&input.reference_dir_sizes(), params))
+    }
+
+    fn reference_second(&self, input: &Self::Input, params: &Params) -> Option<Self::Output2> {
+        Some(smallest_dir_to_delete(&input.reference_dir_sizes(), params))
+    }
+
+    // a session exploring a random tree of about `size` files and directories
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        // keeps the total somewhere around the disk size
+        let max_file_size = (150_000_000 / size.max(1) as u64).clamp(1000, 400_000);
+        let mut out = String::from("$ cd /\n");
+        let mut remaining = size.max(1);
+        generate_dir(rng, &mut out, &mut remaining, 0, max_file_size);
+        Some(out)
+    }
+}
+
+fn generate_name(rng: &mut Rng) -> String {
+    (0..rng.range(1..9))
+        .map(|_| rng.range(b'a' as u64..b'z' as u64 + 1) as u8 as char)
+        .collect()
+}
+
+fn generate_dir(
+    rng: &mut Rng,
+    out: &mut String,
+    remaining: &mut usize,
+    depth: usize,
+    max_file_size: u64,
+) {
+    out.push_str("$ ls\n");
+
+    let files = rng.below(5).min(*remaining);
+    *remaining -= files;
+    let dirs = if depth < 12 {
+        rng.range(1..4).min(*remaining as u64) as usize
+    } else {
+        0
+    };
+    *remaining -= dirs;
+
+    for _ in 0..files {
+        let size = rng.range(1..max_file_size + 1);
+        out.push_str(&format!(
+            "{size} {}.{}\n",
+            generate_name(rng),
+            generate_name(rng)
+        ));
+    }
+    let names = (0..dirs)
+        .map(|i| format!("{}{i}", generate_name(rng)))
+        .collect::<Vec<_>>();
+    for name in &names {
+        out.push_str(&format!("dir {name}\n"));
+    }
+
+    for name in &names {
+        out.push_str(&format!("$ cd {name}\n"));
+        generate_dir(rng, out, remaining, depth + 1, max_file_size);
+        out.push_str("$ cd ..\n");
+    }
+}
+
+fn small_dirs_total(dir_sizes: &[u64], params: &Params) -> u64 {
+    let max_dir_size: u64 = params.get("max_dir_size");
+
+    dir_sizes.iter().filter(|&&s| s <= max_dir_size).sum()
+}
+
+fn smallest_dir_to_delete(dir_sizes: &[u64], params: &Params) -> u64 {
+    let disk_size: u64 = params.get("disk_size");
+    let needed_space: u64 = params.get("needed_space");
+
+    // the root directory comes first
+    let used_space = dir_sizes.first().cloned().unwrap_or_default();
+    let unused_space = disk_size.saturating_sub(used_space);
+    let min_needed_cleanup = needed_space.saturating_sub(unused_space);
+
+    dir_sizes
+        .iter()
+        .filter(|&&s| s >= min_needed_cleanup)
+        .min()
+        .cloned()
+        .unwrap_or_default()
+}
+
+enum Line {
+    Cd(String),
+    Ls,
+    Dir,
+    File(u64),
+}
+
+impl FromStr for Line {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Cursor::parse(s, |c| {
+            c.alt(&[
+                &|c| {
+                    c.literal("$ cd ")?;
+                    Ok(Self::Cd(c.word()?.to_string()))
+                },
+                &|c| c.literal("$ ls").map(|_| Self::Ls),
+                &|c| {
+                    c.literal("dir ")?;
+                    c.word().map(|_| Self::Dir)
+                },
+                &|c| {
+                    let size = c.number()?;
+                    c.literal(" ")?;
+                    c.word().map(|_| Self::File(size))
+                },
+            ])
+        })
     }
 }
 
@@ -49,19 +194,20 @@ pub struct FileSystem {
 }
 
 impl FileSystem {
-    fn from_reader<R: Read>(r: R) -> FileSystem {
+    fn from_reader<R: Read>(r: R) -> Result<FileSystem, Error> {
         let mut fs = Self {
             nodes: vec![],
             children: Default::default(),
         };
         let mut current_node_stack = vec![];
+        // every directory size is at most the total, which then has to fit
+        let mut total_size = 0u64;
 
-        let reader = BufReader::new(r);
-        for line in reader.lines().flatten() {
-            // enter directory, add it to the node list, push exploration stack
-            // or pop the stack if we go up
-            if let Ok(dir) = scan_fmt!(&line, "$ cd {}", String) {
-                match dir.as_str() {
+        for line in r.try_split_lines()? {
+            match line {
+                // enter directory, add it to the node list, push exploration stack
+                // or pop the stack if we go up
+                Line::Cd(dir) => match dir.as_str() {
                     ".." => {
                         current_node_stack.pop();
                     }
@@ -75,20 +221,25 @@ impl FileSystem {
 
                         current_node_stack.push(idx);
                     }
-                }
-            }
-            // we find a file in the current directory, add it to node list
-            if let Ok((size, _)) = scan_fmt!(&line, "{} {}", u64, String) {
-                fs.nodes.push(Node::File(size));
-                let idx = fs.nodes.len() - 1;
+                },
+                // we find a file in the current directory, add it to node list
+                Line::File(size) => {
+                    total_size = total_size
+                        .checked_add(size)
+                        .ok_or_else(|| anyhow!("files too large"))?;
+                    fs.nodes.push(Node::File(size));
+                    let idx = fs.nodes.len() - 1;
 
-                if let Some(&parent_idx) = current_node_stack.last() {
-                    fs.children.entry(parent_idx).or_default().push(idx);
+                    if let Some(&parent_idx) = current_node_stack.last() {
+                        fs.children.entry(parent_idx).or_default().push(idx);
+                    }
                 }
+                // directories are only added when we enter them
+                Line::Ls | Line::Dir => {}
             }
         }
 
-        fs
+        Ok(fs)
     }
 
     fn is_dir(&self, idx: usize) -> bool {
@@ -98,13 +249,65 @@ impl FileSystem {
             .unwrap_or(false)
     }
 
-    fn node_size(&self, idx: usize) -> u64 {
-        if let Some(Node::File(size)) = self.nodes.get(idx) {
-            *size
-        } else if let Some(children) = self.children.get(&idx) {
-            children.iter().map(|&i| self.node_size(i)).sum()
-        } else {
-            0
+    // children are always pushed after their parent, so walking the nodes backwards
+    // computes every size in a single pass
+    fn node_sizes(&self) -> Vec<u64> {
+        let mut sizes = vec![0; self.nodes.len()];
+        for idx in (0..self.nodes.len()).rev() {
+            sizes[idx] = match self.nodes[idx] {
+                Node::File(size) => size,
+                Node::Dir => self
+                    .children
+                    .get(&idx)
+                    .map_or(0, |c| c.iter().map(|&i| sizes[i]).sum()),
+            };
         }
+        sizes
+    }
+
+    // sums up each directory on its own, recursively
+    fn reference_dir_sizes(&self) -> Vec<u64> {
+        fn size(fs: &FileSystem, idx: usize) -> u64 {
+            match fs.nodes[idx] {
+                Node::File(size) => size,
+                Node::Dir => fs
+                    .children
+                    .get(&idx)
+                    .map_or(0, |c| c.iter().map(|&i| size(fs, i)).sum()),
+            }
+        }
+
+        (0..self.nodes.len())
+            .filter(|&idx| self.is_dir(idx))
+            .map(|idx| size(self, idx))
+            .collect()
+    }
+
+    fn dir_sizes(&self) -> Vec<u64> {
+        self.node_sizes()
+            .into_iter()
+            .enumerate()
+            .filter(|&(idx, _)| self.is_dir(idx))
+            .map(|(_, size)| size)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_problem() {
+        let input = include_str!("../../tests/examples/day07/1");
+        let pb = Problem {};
+        let input = pb.parse_input(input.as_bytes()).unwrap();
+
+        assert_eq!(pb.solve_first(&input), 95437);
+        assert_eq!(pb.solve_second(&input), 24933642);
+
+        let params = pb.default_params();
+        let both = pb.solve_both(&input, &params, &mut Stopwatch::start());
+        assert_eq!(both, (95437, 24933642));
     }
 }
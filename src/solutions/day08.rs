@@ -1,8 +1,9 @@
-use crate::grid::Grid;
-use crate::solver::Solver;
-use itertools::Itertools;
+use crate::generate::Rng;
+use crate::grid::{Direction, Grid};
+use crate::solver::{Params, Solver};
+use anyhow::{anyhow, Error};
 use std::io::Read;
-use std::ops::Range;
+use std::iter::once;
 
 pub struct Problem;
 
@@ -11,87 +12,158 @@ impl Solver for Problem {
     type Output1 = usize;
     type Output2 = usize;
 
-    fn parse_input<R: Read>(&self, r: R) -> Self::Input {
-        Grid::from_reader(r).unwrap()
+    fn parse_input<R: Read>(&self, r: R) -> Result<Self::Input, Error> {
+        let grid: Grid<u8> = Grid::from_reader(r)?;
+        if let Some(b) = grid.iter().find(|b| !b.is_ascii_digit()) {
+            return Err(anyhow!("{:?}: invalid tree height", *b as char));
+        }
+        Ok(grid)
     }
 
     fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
-        (0..input.w)
-            .cartesian_product(0..input.h)
-            .filter(|&c| !is_tree_hidden(input, c))
-            .count()
+        visibility_map(input).count(|&v| v)
     }
 
     fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
-        (0..input.w)
-            .cartesian_product(0..input.h)
-            .map(|c| tree_scenic_score(input, c))
+        scenic_map(input).into_iter().max().unwrap_or_default()
+    }
+
+    fn reference_first(&self, input: &Self::Input, _params: &Params) -> Option<Self::Output1> {
+        Some(
+            input
+                .iter_coords()
+                .filter(|&c| {
+                    Direction::ALL
+                        .iter()
+                        .any(|&d| reference_view(input, c, d).1)
+                })
+                .count(),
+        )
+    }
+
+    fn reference_second(&self, input: &Self::Input, _params: &Params) -> Option<Self::Output2> {
+        input
+            .iter_coords()
+            .map(|c| {
+                Direction::ALL
+                    .iter()
+                    .map(|&d| reference_view(input, c, d).0)
+                    .product()
+            })
             .max()
-            .unwrap_or_default()
+            .or(Some(0))
+    }
+
+    // a forest `size` trees wide and high
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        let size = size.max(1);
+        Some(
+            (0..size)
+                .map(|_| {
+                    let mut row = (0..size)
+                        .map(|_| (b'0' + rng.below(10) as u8) as char)
+                        .collect::<String>();
+                    row.push('\n');
+                    row
+                })
+                .collect(),
+        )
     }
 }
 
-fn neighbour_ranges(
-    x: usize,
-    y: usize,
-    w: usize,
-    h: usize,
-) -> (Range<usize>, Range<usize>, Range<usize>, Range<usize>) {
-    ((0..x), (x + 1..w), (0..y), (y + 1..h))
+// walks away from the tree, returning how many trees it sees and whether it sees the edge
+fn reference_view(g: &Grid<u8>, (x, y): (usize, usize), d: Direction) -> (usize, bool) {
+    let height = g.get((x, y)).expect("valid coord");
+    let (mut x, mut y, mut seen) = (x as isize, y as isize, 0);
+    let (dx, dy) = (d.delta().x as isize, d.delta().y as isize);
+
+    loop {
+        x += dx;
+        y += dy;
+        if x < 0 || y < 0 || x >= g.w as isize || y >= g.h as isize {
+            return (seen, true);
+        }
+        seen += 1;
+        if g.get((x as usize, y as usize)).expect("valid coord") >= height {
+            return (seen, false);
+        }
+    }
+}
+
+// every row or column, from the edge on the `d` side of the forest towards the other one
+fn lines_from_edge(
+    g: &Grid<u8>,
+    d: Direction,
+) -> impl Iterator<Item = impl Iterator<Item = ((usize, usize), &u8)>> {
+    g.enumerate()
+        .filter(move |&(c, _)| g.step(c, d).is_none())
+        .map(move |edge| once(edge).chain(g.ray(edge.0, d.opposite())))
+}
+
+// a tree is visible from the edge when it's taller than all the trees swept before it
+fn visibility_map(g: &Grid<u8>) -> Grid<bool> {
+    let mut visible = Grid::new_with(g.w, g.h, false);
+
+    for d in Direction::ALL {
+        for line in lines_from_edge(g, d) {
+            let mut tallest = None;
+            for (c, &height) in line {
+                if tallest < Some(height) {
+                    tallest = Some(height);
+                    *visible.get_mut(c).expect("valid coord") = true;
+                }
+            }
+        }
+    }
+
+    visible
 }
 
-fn is_tree_hidden(g: &Grid<u8>, (x, y): (usize, usize)) -> bool {
-    let current = g.get((x, y)).expect("valid coord");
-
-    let (left, right, top, down) = neighbour_ranges(x, y, g.w, g.h);
-    let l = left
-        .into_iter()
-        .filter_map(|rx| g.get((rx, y)))
-        .any(|v| v >= current);
-    let r = right
-        .into_iter()
-        .filter_map(|rx| g.get((rx, y)))
-        .any(|v| v >= current);
-    let t = top
-        .into_iter()
-        .filter_map(|ry| g.get((x, ry)))
-        .any(|v| v >= current);
-    let d = down
-        .into_iter()
-        .filter_map(|ry| g.get((x, ry)))
-        .any(|v| v >= current);
-
-    l && r && t && d
+// the stack keeps the trees swept so far that no later tree has hidden, shortest on top,
+// so the first one at least as tall as the current tree is what blocks its view
+fn scenic_map(g: &Grid<u8>) -> Grid<usize> {
+    let mut scores = Grid::new_with(g.w, g.h, 1);
+
+    for d in Direction::ALL {
+        for line in lines_from_edge(g, d) {
+            let mut stack: Vec<(u8, usize)> = vec![];
+            for (i, (c, &height)) in line.enumerate() {
+                while stack.last().is_some_and(|&(h, _)| h < height) {
+                    stack.pop();
+                }
+                let seen = stack.last().map_or(i, |&(_, j)| i - j);
+                *scores.get_mut(c).expect("valid coord") *= seen;
+                stack.push((height, i));
+            }
+        }
+    }
+
+    scores
 }
 
-fn tree_scenic_score(g: &Grid<u8>, (x, y): (usize, usize)) -> usize {
-    let current = g.get((x, y)).expect("valid coord");
-
-    let (left, right, top, down) = neighbour_ranges(x, y, g.w, g.h);
-    let (ll, rl, tl, dl) = (left.len(), right.len(), top.len(), down.len());
-
-    let l = left
-        .into_iter()
-        .rev()
-        .position(|rx| g.get((rx, y)).unwrap_or(&0) >= current)
-        .map(|n| n + 1)
-        .unwrap_or(ll);
-    let r = right
-        .into_iter()
-        .position(|rx| g.get((rx, y)).unwrap_or(&0) >= current)
-        .map(|n| n + 1)
-        .unwrap_or(rl);
-    let t = top
-        .into_iter()
-        .rev()
-        .position(|ry| g.get((x, ry)).unwrap_or(&0) >= current)
-        .map(|n| n + 1)
-        .unwrap_or(tl);
-    let d = down
-        .into_iter()
-        .position(|ry| g.get((x, ry)).unwrap_or(&0) >= current)
-        .map(|n| n + 1)
-        .unwrap_or(dl);
-
-    l * r * t * d
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_problem() {
+        let input = include_str!("../../tests/examples/day08/1");
+        let pb = Problem {};
+        let input = pb.parse_input(input.as_bytes()).unwrap();
+
+        assert_eq!(pb.solve_first(&input), 21);
+        assert_eq!(pb.solve_second(&input), 8);
+
+        let visible = visibility_map(&input);
+        assert_eq!(visible.get((1, 1)), Some(&true));
+        assert_eq!(visible.get((2, 2)), Some(&false));
+        let scores = scenic_map(&input);
+        assert_eq!(scores.get((2, 1)), Some(&4));
+        assert_eq!(scores.get((2, 3)), Some(&8));
+    }
+
+    #[test]
+    fn test_ragged_rows() {
+        assert!(Problem.parse_input("123\n45\n6789\n".as_bytes()).is_err());
+    }
 }
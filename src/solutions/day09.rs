@@ -1,7 +1,8 @@
-use crate::solver::{ReadExt, Solver};
-use anyhow::{anyhow, Error};
-use scan_fmt::scan_fmt;
-use std::collections::HashSet;
+use crate::generate::Rng;
+use crate::grid::{Direction, Point, SparseGrid};
+use crate::solver::{Cursor, Params, ParseError, ReadExt, Solver};
+use crate::visualize;
+use anyhow::Error;
 use std::io::Read;
 use std::str::FromStr;
 
@@ -12,95 +13,117 @@ impl Solver for Problem {
     type Output1 = usize;
     type Output2 = usize;
 
-    fn parse_input<R: Read>(&self, r: R) -> Self::Input {
-        r.split_lines()
+    fn parse_input<R: Read>(&self, r: R) -> Result<Self::Input, Error> {
+        r.try_split_lines()
     }
 
     fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
-        solve_for_size(2, input)
+        self.solve_first_with(input, &self.default_params())
     }
 
     fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
-        solve_for_size(10, input)
+        self.solve_second_with(input, &self.default_params())
+    }
+
+    fn default_params(&self) -> Params {
+        Params::new().with("knots1", 2).with("knots2", 10)
+    }
+
+    // a rope needs a tail to follow its head
+    fn check_params(&self, params: &Params) -> Result<(), Error> {
+        params.at_least::<usize>("knots1", 2)?;
+        params.at_least::<usize>("knots2", 2)?;
+        Ok(())
+    }
+
+    fn solve_first_with(&self, input: &Self::Input, params: &Params) -> Self::Output1 {
+        solve_for_size(params.get("knots1"), input)
+    }
+
+    fn solve_second_with(&self, input: &Self::Input, params: &Params) -> Self::Output2 {
+        solve_for_size(params.get("knots2"), input)
+    }
+
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(
+            (0..size.max(1))
+                .map(|_| format!("{} {}\n", rng.pick(&["U", "D", "L", "R"]), rng.range(1..20)))
+                .collect(),
+        )
     }
 }
 
 fn solve_for_size(n: usize, instrs: &[Instr]) -> usize {
-    const ORIGIN: (isize, isize) = (0, 0);
-    let mut knots = vec![ORIGIN; n];
+    let mut knots = vec![Point::ORIGIN; n];
 
-    let mut visited = HashSet::new();
-    visited.insert(ORIGIN);
+    let mut visited = SparseGrid::new();
+    visited.insert(Point::ORIGIN, ());
 
     for instr in instrs {
         for _ in 0..instr.n {
-            knots[0] = move_head(knots[0], &instr.dir);
+            knots[0] += instr.dir.delta();
             for i in 1..n {
                 knots[i] = move_tail(knots[i], knots[i - 1]);
             }
-            visited.insert(knots[n - 1]);
+            visited.insert(knots[n - 1], ());
+            visualize::emit(|| render_rope(&knots, &visited));
         }
     }
 
     visited.len()
 }
 
-fn move_head((x, y): (isize, isize), dir: &Dir) -> (isize, isize) {
-    match dir {
-        Dir::Up => (x, y + 1),
-        Dir::Down => (x, y - 1),
-        Dir::Left => (x - 1, y),
-        Dir::Right => (x + 1, y),
+// same notation as the puzzle: H for the head, then T for the tail of a two knot rope
+// or knot numbers for longer ones, s for the start and # for the positions visited by the tail
+fn render_rope(knots: &[Point], visited: &SparseGrid<()>) -> String {
+    let mut plane = SparseGrid::new();
+    for (p, _) in visited.iter() {
+        plane.insert(p, '#');
     }
+    plane.insert(Point::ORIGIN, 's');
+    // drawn from the tail so that the knots in front stay on top
+    for (i, &k) in knots.iter().enumerate().rev() {
+        let c = match i {
+            0 => 'H',
+            _ if knots.len() == 2 => 'T',
+            i => char::from_digit(i as u32 % 10, 10).unwrap_or('?'),
+        };
+        plane.insert(k, c);
+    }
+    plane.to_string()
 }
 
-fn move_tail((tx, ty): (isize, isize), (hx, hy): (isize, isize)) -> (isize, isize) {
+fn move_tail(t: Point, h: Point) -> Point {
     // close enough, do nothing
-    if hx.abs_diff(tx) <= 1 && hy.abs_diff(ty) <= 1 {
-        (tx, ty)
+    if t.chebyshev(h) <= 1 {
+        t
     } else {
-        let dx = (hx - tx).signum();
-        let dy = (hy - ty).signum();
-        (tx + dx, ty + dy)
+        t + (h - t).signum()
     }
 }
 
 #[derive(Debug)]
 pub struct Instr {
-    dir: Dir,
-    n: isize,
+    dir: Direction,
+    n: usize,
 }
 
 impl FromStr for Instr {
-    type Err = Error;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (dir, n) = scan_fmt!(s, "{} {}", String, isize)?;
-        Ok(Self {
-            dir: dir.parse()?,
-            n,
-        })
-    }
-}
-
-#[derive(Debug)]
-pub enum Dir {
-    Up,
-    Down,
-    Left,
-    Right,
-}
-
-impl FromStr for Dir {
-    type Err = Error;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(match s {
-            "U" => Self::Up,
-            "D" => Self::Down,
-            "L" => Self::Left,
-            "R" => Self::Right,
-            _ => Err(anyhow!("{s}: invalid direction"))?,
+        Cursor::parse(s, |c| {
+            let dir = c.one_of(&[
+                ("U", Direction::Up),
+                ("D", Direction::Down),
+                ("L", Direction::Left),
+                ("R", Direction::Right),
+            ])?;
+            c.literal(" ")?;
+            Ok(Self {
+                dir,
+                n: c.number()?,
+            })
         })
     }
 }
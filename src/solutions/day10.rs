@@ -1,7 +1,8 @@
+use crate::generate::Rng;
 use crate::grid::Grid;
-use crate::solver::{ReadExt, Solver};
-use scan_fmt::scan_fmt;
-use std::convert::Infallible;
+use crate::solver::{Cursor, Params, ParseError, Phase, ReadExt, Solver, Stopwatch};
+use crate::visualize;
+use anyhow::Error;
 use std::fmt::{Display, Formatter};
 use std::io::Read;
 use std::str::FromStr;
@@ -13,36 +14,169 @@ impl Solver for Problem {
     type Output1 = i64;
     type Output2 = String;
 
-    fn parse_input<R: Read>(&self, r: R) -> Self::Input {
-        r.split_lines()
+    fn parse_input<R: Read>(&self, r: R) -> Result<Self::Input, Error> {
+        r.try_split_lines()
     }
 
     fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
-        let s = compute_states(input);
-        [20, 60, 100, 140, 180, 220]
-            .into_iter()
-            .map(|i| s[i - 1] * (i as i64))
-            .sum()
+        self.solve_first_with(input, &self.default_params())
     }
 
     fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
-        let (w, h) = (40, 6);
+        self.solve_second_with(input, &self.default_params())
+    }
+
+    fn default_params(&self) -> Params {
+        Params::new()
+            .with("first_cycle", 20)
+            .with("cycle_step", 40)
+            .with("last_cycle", 220)
+            .with("width", 40)
+            .with("height", 6)
+    }
+
+    fn check_params(&self, params: &Params) -> Result<(), Error> {
+        for key in ["first_cycle", "cycle_step", "last_cycle"] {
+            params.at_least::<usize>(key, 1)?;
+        }
+        params.at_least::<usize>("width", 1)?;
+        params.at_least::<usize>("height", 1)?;
+        Ok(())
+    }
 
-        let mut screen = Grid::new_with(w, h, Pixel::Dark);
-        let screen_as_slice = screen.as_slice_mut();
+    fn solve_first_with(&self, input: &Self::Input, params: &Params) -> Self::Output1 {
+        signal_strength(&compute_states(input), params)
+    }
 
+    fn solve_second_with(&self, input: &Self::Input, params: &Params) -> Self::Output2 {
+        render_screen(&compute_states(input), params)
+    }
+
+    fn solve_both(
+        &self,
+        input: &Self::Input,
+        params: &Params,
+        sw: &mut Stopwatch,
+    ) -> (Self::Output1, Self::Output2) {
         let states = compute_states(input);
+        sw.lap(Phase::Shared);
+        let s1 = signal_strength(&states, params);
+        sw.lap(Phase::First);
+        let s2 = render_screen(&states, params);
+        sw.lap(Phase::Second);
+        (s1, s2)
+    }
 
-        for (cycle, &x) in states.iter().enumerate() {
-            let c = cycle as i64 % w as i64;
-            let x = x % w as i64;
-            if [x - 1, x, x + 1].contains(&c) {
-                screen_as_slice[cycle] = Pixel::Lit;
+    fn reference_first(&self, input: &Self::Input, params: &Params) -> Option<Self::Output1> {
+        Some(reference_run(input, params).0)
+    }
+
+    fn reference_second(&self, input: &Self::Input, params: &Params) -> Option<Self::Output2> {
+        Some(reference_run(input, params).1)
+    }
+
+    // at least `size` instructions, and enough of them to draw the whole screen
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        let (mut out, mut cycles, mut x, mut n) = (String::new(), 0, 1i64, 0);
+
+        while n < size || cycles < 240 {
+            if rng.chance(0.4) {
+                out.push_str("noop\n");
+                cycles += 1;
+            } else {
+                // drifts towards the middle of the screen so the sprite stays visible
+                let v = rng.range(0..21) as i64 - if x > 20 { 15 } else { 5 };
+                x += v;
+                out.push_str(&format!("addx {v}\n"));
+                cycles += 2;
             }
+            n += 1;
+        }
+
+        Some(out)
+    }
+}
+
+// the register keeps its value once the program is over
+fn x_during(states: &[i64], cycle: usize) -> i64 {
+    states
+        .get(cycle - 1)
+        .or(states.last())
+        .cloned()
+        .unwrap_or(1)
+}
+
+fn signal_strength(states: &[i64], params: &Params) -> i64 {
+    let first: usize = params.get("first_cycle");
+    let step: usize = params.get("cycle_step");
+    let last: usize = params.get("last_cycle");
+
+    (first..=last)
+        .step_by(step)
+        .map(|i| x_during(states, i) * (i as i64))
+        .sum()
+}
+
+fn render_screen(states: &[i64], params: &Params) -> String {
+    let (w, h): (usize, usize) = (params.get("width"), params.get("height"));
+
+    let mut screen = Grid::new_with(w, h, Pixel::Dark);
+
+    for cycle in 1..=w * h {
+        let c = ((cycle - 1) % w) as i64;
+        let x = x_during(states, cycle);
+        if [x - 1, x, x + 1].contains(&c) {
+            screen.as_slice_mut()[cycle - 1] = Pixel::Lit;
         }
+        visualize::emit_grid(&screen);
+    }
+
+    format!("\n{screen}")
+}
 
-        format!("\n{screen}")
+// runs the CPU one cycle at a time, looking at the register during each cycle
+fn reference_run(program: &[Instr], params: &Params) -> (i64, String) {
+    let first: usize = params.get("first_cycle");
+    let step: usize = params.get("cycle_step");
+    let last: usize = params.get("last_cycle");
+    let (w, h): (usize, usize) = (params.get("width"), params.get("height"));
+
+    let mut strength = 0;
+    let mut screen = vec![vec!['.'; w]; h];
+    let mut during_cycle = |cycle: usize, x: i64| {
+        if cycle >= first && cycle <= last && (cycle - first) % step == 0 {
+            strength += cycle as i64 * x;
+        }
+        if cycle <= w * h {
+            let (row, col) = ((cycle - 1) / w, (cycle - 1) % w);
+            if (col as i64 - x).abs() <= 1 {
+                screen[row][col] = '#';
+            }
+        }
+    };
+
+    let (mut cycle, mut x) = (0, 1);
+    for instr in program {
+        let (cycles, dx) = match instr {
+            Instr::Noop => (1, 0),
+            Instr::Addx(n) => (2, *n),
+        };
+        for _ in 0..cycles {
+            cycle += 1;
+            during_cycle(cycle, x);
+        }
+        x += dx;
+    }
+    while cycle < last.max(w * h) {
+        cycle += 1;
+        during_cycle(cycle, x);
     }
+
+    let screen = screen
+        .into_iter()
+        .map(|r| r.into_iter().collect::<String>() + "\n")
+        .collect::<String>();
+    (strength, format!("\n{screen}"))
 }
 
 fn compute_states(program: &[Instr]) -> Vec<i64> {
@@ -65,13 +199,15 @@ pub enum Instr {
 }
 
 impl FromStr for Instr {
-    type Err = Infallible;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(if let Ok(n) = scan_fmt!(s, "addx {}", i64) {
-            Self::Addx(n)
-        } else {
-            Self::Noop
+        Cursor::parse(s, |c| {
+            c.alt(&[&|c| c.literal("noop").map(|_| Self::Noop), &|c| {
+                c.literal("addx ")?;
+                // i32 so that the register can't overflow
+                Ok(Self::Addx(c.number::<i32>()?.into()))
+            }])
         })
     }
 }
@@ -94,3 +230,25 @@ impl Display for Pixel {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::assert_snapshot;
+
+    #[test]
+    fn test_problem() {
+        let input = include_str!("../../tests/examples/day10/1");
+        let pb = Problem {};
+        let input = pb.parse_input(input.as_bytes()).unwrap();
+
+        assert_eq!(pb.solve_first(&input), 13140);
+        let screen = pb.solve_second(&input);
+        assert_snapshot("day10_screen", &screen);
+
+        let params = pb.default_params();
+        let (s1, s2) = pb.solve_both(&input, &params, &mut Stopwatch::start());
+        assert_eq!(s1, 13140);
+        assert_eq!(s2, screen);
+    }
+}
@@ -1,7 +1,8 @@
-use crate::solver::{ReadExt, Solver};
-use anyhow::Error;
+use crate::generate::Rng;
+use crate::solver::{Cursor, Params, ParseError, ReadExt, Solver};
+use crate::trace;
+use anyhow::{anyhow, Error};
 use itertools::Itertools;
-use scan_fmt::scan_fmt;
 use std::collections::btree_map::BTreeMap;
 use std::io::Read;
 use std::str::FromStr;
@@ -13,49 +14,169 @@ impl Solver for Problem {
     type Output1 = usize;
     type Output2 = usize;
 
-    fn parse_input<R: Read>(&self, r: R) -> Self::Input {
-        r.split_groups()
+    fn parse_input<R: Read>(&self, r: R) -> Result<Self::Input, Error> {
+        let monkeys: Vec<Monkey> = r.try_split_groups()?;
+        for (i, m) in monkeys.iter().enumerate() {
+            let (a, b) = m.next_monkey;
+            if a >= monkeys.len() || b >= monkeys.len() || a == i || b == i {
+                return Err(anyhow!("monkey {i}: throws to an unknown monkey or itself"));
+            }
+            if m.test_divisible_by == 0 {
+                return Err(anyhow!("monkey {i}: divisible by 0"));
+            }
+        }
+
+        // part 2 keeps the worry levels below the product of the divisors
+        let product = monkeys
+            .iter()
+            .try_fold(1u64, |p, m| p.checked_mul(m.test_divisible_by));
+        if !product.is_some_and(|p| p <= u32::MAX as u64) {
+            return Err(anyhow!("divisors too large"));
+        }
+        Ok(monkeys)
     }
 
     fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
-        let mut monkeys = input.clone();
+        self.solve_first_with(input, &self.default_params())
+    }
 
-        for _ in 0..20 {
-            evaluate_round(&mut monkeys, |v| v / 3);
-        }
+    fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
+        self.solve_second_with(input, &self.default_params())
+    }
 
-        monkeys
-            .iter()
-            .map(|m| m.total_examined)
-            .sorted_unstable()
-            .rev()
-            .take(2)
-            .product()
+    fn default_params(&self) -> Params {
+        Params::new()
+            .with("rounds1", 20)
+            .with("rounds2", 10000)
+            .with("relief", 3)
     }
 
-    fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
-        let mut monkeys = input.clone();
+    fn check_params(&self, params: &Params) -> Result<(), Error> {
+        params.at_least::<usize>("rounds1", 1)?;
+        params.at_least::<usize>("rounds2", 1)?;
+        params.at_least::<u64>("relief", 1)?;
+        Ok(())
+    }
+
+    // the worry levels of part 1 aren't bounded, they must fit in a u64 for these rounds
+    fn check_input(&self, input: &Self::Input, params: &Params) -> Result<(), Error> {
+        let relief: u64 = params.get("relief");
+        if play(input, params.get("rounds1"), |v| v / relief).is_none() {
+            return Err(anyhow!(
+                "worry levels of part 1 overflow with these parameters"
+            ));
+        }
+        Ok(())
+    }
+
+    fn solve_first_with(&self, input: &Self::Input, params: &Params) -> Self::Output1 {
+        let relief: u64 = params.get("relief");
+
+        // check_input rejected the inputs that overflow
+        play(input, params.get("rounds1"), |v| v / relief).map_or(0, |m| monkey_business(&m))
+    }
 
+    fn solve_second_with(&self, input: &Self::Input, params: &Params) -> Self::Output2 {
         // find LCM for all monkeys (they're prime, so it's just the product)
-        let lcm = monkeys.iter().map(|m| m.test_divisible_by).product::<u64>();
+        let lcm = input.iter().map(|m| m.test_divisible_by).product::<u64>();
 
         // wrapping each integer to its modulo works, because all the operations either
-        // preserve the modulo via multiplying, or add a fixed amount so modulo will stay the same
-        for _ in 0..10000 {
-            evaluate_round(&mut monkeys, |v| v % lcm);
-        }
+        // preserve the modulo via multiplying, or add a fixed amount so modulo will stay the same;
+        // parsing made sure the product fits in 32 bits, so nothing overflows
+        play(input, params.get("rounds2"), |v| v % lcm).map_or(0, |m| monkey_business(&m))
+    }
 
-        monkeys
+    // up to eight monkeys sharing about `size` items, with distinct prime divisors; retries
+    // until the worry levels of part 1 fit in a u64
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        let n = (size / 10).clamp(2, 8);
+        let params = self.default_params();
+
+        let monkeys = loop {
+            let monkeys = generate_monkeys(rng, n, size);
+            if self.check_input(&monkeys, &params).is_ok() {
+                break monkeys;
+            }
+        };
+
+        let monkeys = monkeys
             .iter()
-            .map(|m| m.total_examined)
-            .sorted_unstable()
-            .rev()
-            .take(2)
-            .product()
+            .enumerate()
+            .map(|(i, m)| {
+                let operation = match m.operation {
+                    Operation::Add(k) => format!("old + {k}"),
+                    Operation::Mul(k) => format!("old * {k}"),
+                    Operation::Square => "old * old".to_string(),
+                };
+                format!(
+                    "Monkey {i}:\n  Starting items: {}\n  Operation: new = {operation}\n  Test: divisible by {}\n    If true: throw to monkey {}\n    If false: throw to monkey {}\n",
+                    m.items.iter().join(", "),
+                    m.test_divisible_by,
+                    m.next_monkey.0,
+                    m.next_monkey.1
+                )
+            })
+            .collect::<Vec<_>>();
+
+        Some(monkeys.join("\n"))
+    }
+}
+
+fn generate_monkeys(rng: &mut Rng, n: usize, size: usize) -> Vec<Monkey> {
+    // the product of the divisors stays below 2^32, so squares modulo it fit in a u64
+    let mut primes = vec![2, 3, 5, 7, 11, 13, 17, 19, 23];
+    rng.shuffle(&mut primes);
+    let square = rng.below(n);
+
+    (0..n)
+        .map(|i| {
+            // throws to two other monkeys, which are the same one when there are only two
+            let mut others = (0..n).filter(|&j| j != i).collect::<Vec<_>>();
+            rng.shuffle(&mut others);
+            let next_monkey = (others[0], *others.get(1).unwrap_or(&others[0]));
+
+            Monkey {
+                items: (0..rng.range(1..(2 * size / n).max(1) as u64 + 1))
+                    .map(|_| rng.range(50..100))
+                    .collect(),
+                operation: match rng.below(3) {
+                    _ if i == square => Operation::Square,
+                    0 => Operation::Mul(rng.range(2..20)),
+                    _ => Operation::Add(rng.range(1..9)),
+                },
+                test_divisible_by: primes[i],
+                next_monkey,
+                total_examined: 0,
+            }
+        })
+        .collect()
+}
+
+// the monkeys after the rounds, none if a worry level doesn't fit in a u64
+fn play<F>(monkeys: &[Monkey], rounds: usize, value_control: F) -> Option<Vec<Monkey>>
+where
+    F: Fn(u64) -> u64,
+{
+    let mut monkeys = monkeys.to_vec();
+    for _ in 0..rounds {
+        evaluate_round(&mut monkeys, &value_control)?;
     }
+    Some(monkeys)
 }
 
-fn evaluate_turn<F>(monkeys: &mut [Monkey], i: usize, value_control: F)
+fn monkey_business(monkeys: &[Monkey]) -> usize {
+    let _span = trace::span("monkey_business");
+
+    monkeys
+        .iter()
+        .map(|m| m.total_examined)
+        .sorted_unstable()
+        .rev()
+        .take(2)
+        .product()
+}
+
+fn evaluate_turn<F>(monkeys: &mut [Monkey], i: usize, value_control: F) -> Option<()>
 where
     F: Fn(u64) -> u64,
 {
@@ -63,8 +184,7 @@ where
     let mut moves = BTreeMap::new();
 
     for (idx, item) in m.items.iter().enumerate() {
-        let worry = m.operation.apply(*item);
-        let worry = value_control(worry);
+        let worry = value_control(m.operation.apply(*item)?);
 
         let next_monkey = if (worry % m.test_divisible_by) == 0 {
             m.next_monkey.0
@@ -79,15 +199,19 @@ where
         let _ = monkeys[i].items.remove(item_idx);
         monkeys[next_monkey].items.push(worry);
     }
+    Some(())
 }
 
-fn evaluate_round<F>(monkeys: &mut [Monkey], value_control: F)
+fn evaluate_round<F>(monkeys: &mut [Monkey], value_control: F) -> Option<()>
 where
     F: Fn(u64) -> u64,
 {
+    let _span = trace::span("evaluate_round");
+
     for i in 0..monkeys.len() {
-        evaluate_turn(monkeys, i, &value_control);
+        evaluate_turn(monkeys, i, &value_control)?;
     }
+    Some(())
 }
 
 #[derive(Clone, Debug)]
@@ -103,35 +227,70 @@ impl FromStr for Monkey {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut m = Self {
-            items: vec![],
-            operation: Operation::Square,
-            test_divisible_by: 0,
-            next_monkey: (0, 0),
-            total_examined: 0,
-        };
+        let mut lines = s.lines().enumerate();
 
-        for line in s.lines() {
-            if let Ok(items_str) = scan_fmt!(line, "  Starting items: {[0-9, ]}", String) {
-                m.items
-                    .extend(items_str.split(", ").flat_map(|s| s.parse::<u64>()));
-            } else if let Ok(n) = scan_fmt!(line, "  Operation: new = old * {}", u64) {
-                m.operation = Operation::Mul(n);
-            } else if let Ok(n) = scan_fmt!(line, "  Operation: new = old + {}", u64) {
-                m.operation = Operation::Add(n);
-            } else if let Ok(n) = scan_fmt!(line, "  Test: divisible by {}", u64) {
-                m.test_divisible_by = n;
-            } else if let Ok(n) = scan_fmt!(line, "    If true: throw to monkey {}", usize) {
-                m.next_monkey.0 = n;
-            } else if let Ok(n) = scan_fmt!(line, "    If false: throw to monkey {}", usize) {
-                m.next_monkey.1 = n;
-            }
-        }
+        next_line(&mut lines, |c| {
+            c.literal("Monkey ")?;
+            c.number::<usize>()?;
+            c.literal(":")
+        })?;
+        let items = next_line(&mut lines, |c| {
+            c.literal("  Starting items: ")?;
+            c.list(", ", worry_number)
+        })?;
+        let operation = next_line(&mut lines, |c| {
+            c.literal("  Operation: new = old ")?;
+            c.alt(&[
+                &|c| c.literal("* old").map(|_| Operation::Square),
+                &|c| {
+                    c.literal("* ")?;
+                    Ok(Operation::Mul(worry_number(c)?))
+                },
+                &|c| {
+                    c.literal("+ ")?;
+                    Ok(Operation::Add(worry_number(c)?))
+                },
+            ])
+        })?;
+        let test_divisible_by = next_line(&mut lines, |c| {
+            c.literal("  Test: divisible by ")?;
+            worry_number(c)
+        })?;
+        let if_true = next_line(&mut lines, |c| {
+            c.literal("    If true: throw to monkey ")?;
+            c.number()
+        })?;
+        let if_false = next_line(&mut lines, |c| {
+            c.literal("    If false: throw to monkey ")?;
+            c.number()
+        })?;
 
-        Ok(m)
+        Ok(Self {
+            items,
+            operation,
+            test_divisible_by,
+            next_monkey: (if_true, if_false),
+            total_examined: 0,
+        })
     }
 }
 
+// numbers fit in 32 bits, so that multiplying two of them can't overflow
+fn worry_number(c: &mut Cursor) -> Result<u64, ParseError> {
+    c.number::<u32>().map(u64::from)
+}
+
+// parses the next line of a monkey description, which must be there
+fn next_line<'a, T, F>(lines: &mut impl Iterator<Item = (usize, &'a str)>, f: F) -> Result<T, Error>
+where
+    F: FnOnce(&mut Cursor<'a>) -> Result<T, ParseError>,
+{
+    let (i, line) = lines
+        .next()
+        .ok_or_else(|| anyhow!("unexpected end of monkey"))?;
+    Cursor::parse(line, f).map_err(|e| anyhow!("line {}: {e}", i + 1))
+}
+
 #[derive(Clone, Debug)]
 pub enum Operation {
     Add(u64),
@@ -140,11 +299,42 @@ pub enum Operation {
 }
 
 impl Operation {
-    fn apply(&self, val: u64) -> u64 {
+    // none when the worry level doesn't fit in a u64
+    fn apply(&self, val: u64) -> Option<u64> {
         match self {
-            Operation::Add(n) => val + *n,
-            Operation::Mul(n) => val * *n,
-            Operation::Square => val * val,
+            Operation::Add(n) => val.checked_add(*n),
+            Operation::Mul(n) => val.checked_mul(*n),
+            Operation::Square => val.checked_mul(val),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_input() {
+        let input = include_str!("../../tests/examples/day11/1");
+        let pb = Problem {};
+        let input = pb.parse_input(input.as_bytes()).unwrap();
+
+        let params = pb.default_params();
+        assert!(pb.check_input(&input, &params).is_ok());
+        assert!(pb.check_input(&input, &params.with("relief", 1)).is_err());
+    }
+
+    #[test]
+    fn test_overflow() {
+        let monkey = |i, next| {
+            format!(
+                "Monkey {i}:\n  Starting items: 4000000000\n  Operation: new = old * old\n  Test: divisible by 2\n    If true: throw to monkey {next}\n    If false: throw to monkey {next}\n"
+            )
+        };
+        let input = format!("{}\n{}", monkey(0, 1), monkey(1, 0));
+        let input = Problem.parse_input(input.as_bytes()).unwrap();
+        assert!(Problem
+            .check_input(&input, &Problem.default_params())
+            .is_err());
+    }
+}
@@ -1,9 +1,10 @@
-use crate::grid::Grid;
-use crate::solver::Solver;
+use crate::generate::Rng;
+use crate::grid::{Direction, Grid};
+use crate::search::{distances, grid_moves4, Bfs};
+use crate::solver::{Params, Solver};
+use crate::visualize;
 use anyhow::{anyhow, Error};
-use itertools::Itertools;
-use std::collections::vec_deque::VecDeque;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Display, Formatter};
 use std::io::Read;
 
@@ -14,78 +15,188 @@ impl Solver for Problem {
     type Output1 = usize;
     type Output2 = usize;
 
-    fn parse_input<R: Read>(&self, r: R) -> Self::Input {
-        Grid::from_reader(r).expect("valid grid")
+    fn parse_input<R: Read>(&self, r: R) -> Result<Self::Input, Error> {
+        let grid: Grid<Mark> = Grid::from_reader(r)?;
+        let count = |mark: Mark| grid.count(|m| *m == mark);
+        if count(Mark::Start) != 1 || count(Mark::Exit) == 0 {
+            return Err(anyhow!("expected one start and at least one exit"));
+        }
+        Ok(grid)
+    }
+
+    fn check_input(&self, input: &Self::Input, _params: &Params) -> Result<(), Error> {
+        let from = input.find(|m| *m == Mark::Start).expect("origin present");
+        if find_shortest_path_to_exit(input, from).is_none() {
+            return Err(anyhow!("no path from the start to an exit"));
+        }
+        Ok(())
     }
 
     fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
-        let from = find_origin(input).expect("origin present");
-        find_shortest_path_to_exit(input, from)
+        let from = input.find(|m| *m == Mark::Start).expect("origin present");
+        let path = find_shortest_path_to_exit(input, from).expect("exit reachable");
+        visualize::emit(|| render_path(input, &path));
+        path.len() - 1
     }
 
     fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
-        (0..input.w)
-            .cartesian_product(0..input.h)
-            .filter(|coord| is_possible_start(input, coord))
-            .map(|coord| find_shortest_path_to_exit(input, coord))
+        let dist = distances_to_exit(input);
+        input
+            .positions(is_possible_start)
+            .filter_map(|coord| dist.get(&coord).cloned())
             .min()
-            .expect("a minimum")
+            .expect("exit reachable from the start")
     }
-}
-
-fn is_possible_start(grid: &Grid<Mark>, coord: &(usize, usize)) -> bool {
-    matches!(grid.get(coord), Some(Mark::Start | Mark::Elevation(b'a')))
-}
 
-fn find_origin(grid: &Grid<Mark>) -> Option<(usize, usize)> {
-    (0..grid.w)
-        .cartesian_product(0..grid.h)
-        .find(|coord| matches!(grid.get(coord), Some(Mark::Start)))
-}
+    fn reference_first(&self, input: &Self::Input, _params: &Params) -> Option<Self::Output1> {
+        let from = input.find(|m| *m == Mark::Start).expect("origin present");
+        reference_distances(input).get(from).cloned().flatten()
+    }
 
-fn find_shortest_path_to_exit(grid: &Grid<Mark>, from: (usize, usize)) -> usize {
-    // Dijkstra to the rescue
-    let mut unvisited = HashSet::new();
-    for y in 0..grid.h {
-        for x in 0..grid.w {
-            unvisited.insert((x, y));
-        }
+    fn reference_second(&self, input: &Self::Input, _params: &Params) -> Option<Self::Output2> {
+        let dist = reference_distances(input);
+        input
+            .positions(is_possible_start)
+            .filter_map(|coord| dist.get(coord).cloned().flatten())
+            .min()
     }
 
-    let mut total_cost = usize::MAX;
-    let mut queue = VecDeque::from(vec![(from, 0)]);
+    // a `size` wide heightmap, with a climbable path carved from S in the top left corner
+    // to E in the bottom right one
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        let (w, h) = (size.max(26), (size / 5).max(5));
+        let mut rows = (0..h)
+            .map(|_| {
+                (0..w)
+                    .map(|_| rng.range(b'a' as u64..b'z' as u64 + 1) as u8)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        // a random staircase of right and down steps, long enough to climb from a to z
+        let mut steps = [vec![(1, 0); w - 1], vec![(0, 1); h - 1]].concat();
+        rng.shuffle(&mut steps);
 
-    while let Some(((x, y), cost)) = queue.pop_front() {
-        let pt = (x, y);
-        if !unvisited.contains(&pt) {
-            continue;
+        let len = steps.len();
+        let (mut x, mut y) = (0, 0);
+        rows[0][0] = b'S';
+        for (i, (dx, dy)) in steps.into_iter().enumerate() {
+            x += dx;
+            y += dy;
+            rows[y][x] = b'a' + ((i + 1) * 25 / len) as u8;
         }
+        rows[h - 1][w - 1] = b'E';
 
-        unvisited.remove(&pt);
-
-        match grid.get(&pt) {
-            Some(current @ (Mark::Start | Mark::Elevation(_))) => {
-                let neighbours = grid.neighbours_coords4(pt);
-                for n in neighbours {
-                    if let Some(m) = grid.get(n) {
-                        if current.can_go_to(m) {
-                            queue.push_back((n, cost + 1));
-                        }
-                    }
+        Some(
+            rows.into_iter()
+                .map(|r| String::from_utf8(r).unwrap_or_default() + "\n")
+                .collect(),
+        )
+    }
+}
+
+// distances to the exit, relaxed over the whole map until they don't change; none for
+// the cells that can't reach it
+fn reference_distances(grid: &Grid<Mark>) -> Grid<Option<usize>> {
+    let height = |m: &Mark| match m {
+        Mark::Elevation(e) => *e,
+        Mark::Start => b'a',
+        Mark::Exit => b'z',
+    };
+    let mut dist = grid.map(|_, m| (*m == Mark::Exit).then_some(0));
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (c, m) in grid.enumerate() {
+            let h = height(m);
+            for (n, to) in grid.neighbours_with_coords4(c) {
+                let climbable = height(to) <= h + 1;
+                let via = dist.get(n).expect("valid coord").map(|d| d + 1);
+                let current = dist.get_mut(c).expect("valid coord");
+                if climbable && via.is_some_and(|v| !current.is_some_and(|d| d <= v)) {
+                    *current = via;
+                    changed = true;
                 }
             }
-            Some(Mark::Exit) => {
-                // it's over
-                total_cost = cost;
-                break;
+        }
+    }
+
+    dist
+}
+
+fn is_possible_start(m: &Mark) -> bool {
+    matches!(m, Mark::Start | Mark::Elevation(b'a'))
+}
+
+// the cells from the start to the exit, both included
+fn find_shortest_path_to_exit(
+    grid: &Grid<Mark>,
+    from: (usize, usize),
+) -> Option<Vec<(usize, usize)>> {
+    let mut search = Bfs::new(from, |&c| grid_moves4(grid, c, Mark::can_go_to));
+    // only kept for the frames
+    let mut visited = visualize::enabled().then(HashSet::new);
+    let mut frontier_cost = 0;
+
+    while let Some((pt, cost)) = search.next() {
+        if let Some(visited) = &mut visited {
+            // one frame per BFS level
+            if cost != frontier_cost {
+                frontier_cost = cost;
+                visualize::emit(|| render_search(grid, visited));
             }
-            None => {}
+            visited.insert(pt);
         }
+
+        if matches!(grid.get(pt), Some(Mark::Exit)) {
+            return search.path_to(&pt);
+        }
+    }
+
+    None
+}
+
+// like the puzzle statement: each cell of the path has an arrow towards the next one
+fn render_path(grid: &Grid<Mark>, path: &[(usize, usize)]) -> String {
+    let mut map = Grid::new_with(grid.w, grid.h, '.');
+    for w in path.windows(2) {
+        let dir = Direction::ALL
+            .into_iter()
+            .find(|&d| grid.step(w[0], d) == Some(w[1]));
+        if let (Some(cell), Some(dir)) = (map.get_mut(w[0]), dir) {
+            *cell = dir.arrow();
+        }
+    }
+    if let Some(cell) = path.last().and_then(|&c| map.get_mut(c)) {
+        *cell = 'E';
     }
+    map.to_string()
+}
 
-    total_cost
+// a single search going downhill from the exits, which reaches every cell that can climb
+// to one of them
+fn distances_to_exit(grid: &Grid<Mark>) -> HashMap<(usize, usize), usize> {
+    let exits = grid.positions(|m| *m == Mark::Exit);
+    distances(exits, |&c| {
+        grid_moves4(grid, c, |from, to| to.can_go_to(from))
+    })
+}
+
+// visited cells are drawn in uppercase
+fn render_search(grid: &Grid<Mark>, visited: &HashSet<(usize, usize)>) -> String {
+    grid.map(|c, m| {
+        let m = m.to_string().remove(0);
+        if visited.contains(&c) {
+            m.to_ascii_uppercase()
+        } else {
+            m
+        }
+    })
+    .to_string()
 }
 
+#[derive(Debug, Eq, PartialEq)]
 pub enum Mark {
     Elevation(u8),
     Start,
@@ -107,14 +218,19 @@ impl Display for Mark {
 }
 
 impl Mark {
-    fn can_go_to(&self, other: &Self) -> bool {
-        match (self, other) {
-            (Self::Elevation(a), Self::Elevation(b)) => a > b || a.abs_diff(*b) <= 1,
-            (Self::Start, Self::Elevation(b)) => b'a'.abs_diff(*b) <= 1,
-            (Self::Elevation(a), Self::Exit) => a.abs_diff(b'z') <= 1,
-            _ => false,
+    // the start is at elevation a and the exit at z
+    fn height(&self) -> u8 {
+        match self {
+            Self::Elevation(e) => *e,
+            Self::Start => b'a',
+            Self::Exit => b'z',
         }
     }
+
+    // any way down, but at most one up
+    fn can_go_to(&self, other: &Self) -> bool {
+        other.height() <= self.height() + 1
+    }
 }
 
 impl TryFrom<u8> for Mark {
@@ -129,3 +245,32 @@ impl TryFrom<u8> for Mark {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::assert_snapshot;
+
+    #[test]
+    fn test_problem() {
+        let input = include_str!("../../tests/examples/day12/1");
+        let pb = Problem {};
+        let input = pb.parse_input(input.as_bytes()).unwrap();
+
+        assert_eq!(pb.solve_first(&input), 31);
+        assert_eq!(pb.solve_second(&input), 29);
+
+        let from = input.find(|m| *m == Mark::Start).unwrap();
+        let path = find_shortest_path_to_exit(&input, from).unwrap();
+        assert_eq!(path.len(), 32);
+        assert_snapshot("day12_path", &render_path(&input, &path));
+
+        let params = pb.default_params();
+        assert!(pb.check_input(&input, &params).is_ok());
+        for unreachable in ["SE\n", "SbE\n"] {
+            let input = pb.parse_input(unreachable.as_bytes()).unwrap();
+            assert!(pb.check_input(&input, &params).is_err());
+        }
+        assert!(pb.parse_input("aE\n".as_bytes()).is_err());
+    }
+}
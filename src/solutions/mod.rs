@@ -1,35 +1,37 @@
 // DO NOT EDIT THIS FILE
-use crate::solver::Solver;
+use crate::solver::Runner;
 
-mod day01;
-mod day02;
-mod day03;
-mod day04;
-mod day05;
-mod day06;
-mod day07;
-mod day08;
-mod day09;
-mod day10;
-mod day11;
-mod day12;
+pub mod day01;
+pub mod day02;
+pub mod day03;
+pub mod day04;
+pub mod day05;
+pub mod day06;
+pub mod day07;
+pub mod day08;
+pub mod day09;
+pub mod day10;
+pub mod day11;
+pub mod day12;
 
 
-pub fn exec_day(day: u32) {
+pub const DAYS: &[u32] = &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12];
+
+pub fn solver(day: u32) -> Option<Box<dyn Runner>> {
     match day {
-        1 => day01::Problem {}.solve(day),
-        2 => day02::Problem {}.solve(day),
-        3 => day03::Problem {}.solve(day),
-        4 => day04::Problem {}.solve(day),
-        5 => day05::Problem {}.solve(day),
-        6 => day06::Problem {}.solve(day),
-        7 => day07::Problem {}.solve(day),
-        8 => day08::Problem {}.solve(day),
-        9 => day09::Problem {}.solve(day),
-        10 => day10::Problem {}.solve(day),
-        11 => day11::Problem {}.solve(day),
-        12 => day12::Problem {}.solve(day),
+        1 => Some(Box::new(day01::Problem {})),
+        2 => Some(Box::new(day02::Problem {})),
+        3 => Some(Box::new(day03::Problem {})),
+        4 => Some(Box::new(day04::Problem {})),
+        5 => Some(Box::new(day05::Problem {})),
+        6 => Some(Box::new(day06::Problem {})),
+        7 => Some(Box::new(day07::Problem {})),
+        8 => Some(Box::new(day08::Problem {})),
+        9 => Some(Box::new(day09::Problem {})),
+        10 => Some(Box::new(day10::Problem {})),
+        11 => Some(Box::new(day11::Problem {})),
+        12 => Some(Box::new(day12::Problem {})),
 
-        d => println!("Day {d} hasn't been solved yet :("),
+        _ => None,
     }
 }
@@ -1,44 +1,640 @@
-use std::fmt::Display;
-use std::fs::File;
+use crate::difftest;
+use crate::difftest::DiffOptions;
+use crate::generate::Rng;
+use crate::repl;
+use crate::trace;
+use anyhow::{anyhow, Error};
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Display, Formatter};
+use std::fs::{read_dir, File};
 use std::io;
 use std::io::{BufRead, BufReader, Read};
-use std::path::Path;
+use std::iter::once;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
-fn input_file(day: u32) -> String {
+pub fn input_file(day: u32) -> String {
     format!("input/day{:02}", day)
 }
 
+pub fn examples_dir(day: u32) -> String {
+    format!("tests/examples/day{:02}", day)
+}
+
+pub fn example_files(day: u32) -> Vec<PathBuf> {
+    let mut files = read_dir(examples_dir(day))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_file())
+        .collect::<Vec<_>>();
+    files.sort_unstable();
+    files
+}
+
+// strips a UTF-8 BOM, turns CRLF and CR line endings into LF, removes trailing
+// whitespace on each line and blank lines at the end, so that inputs saved on
+// other platforms parse the same way
+pub fn normalize_input(bytes: &[u8]) -> Vec<u8> {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut it = bytes.iter().peekable();
+    while let Some(&b) = it.next() {
+        match b {
+            b'\r' | b'\n' => {
+                if b == b'\r' && it.peek() == Some(&&b'\n') {
+                    it.next();
+                }
+                while out.ends_with(b" ") || out.ends_with(b"\t") {
+                    out.pop();
+                }
+                out.push(b'\n');
+            }
+            _ => out.push(b),
+        }
+    }
+
+    while out.last().is_some_and(|b| b.is_ascii_whitespace()) {
+        out.pop();
+    }
+    if !out.is_empty() {
+        out.push(b'\n');
+    }
+
+    out
+}
+
 pub trait Solver {
-    type Input;
+    type Input: Debug;
     type Output1: Display;
     type Output2: Display;
 
-    fn parse_input<R: Read>(&self, r: R) -> Self::Input;
+    // set for days that need the input bytes exactly as they are in the file
+    const RAW_INPUT: bool = false;
+
+    fn parse_input<R: Read>(&self, r: R) -> Result<Self::Input, Error>;
     fn solve_first(&self, input: &Self::Input) -> Self::Output1;
     fn solve_second(&self, input: &Self::Input) -> Self::Output2;
 
-    fn load_input<P: AsRef<Path>>(&self, p: P) -> io::Result<Self::Input> {
-        let f = File::open(p)?;
-        Ok(self.parse_input(f))
+    // puzzle constants that can be overridden from the command line
+    fn default_params(&self) -> Params {
+        Params::default()
     }
 
-    fn solve(&self, day: u32) {
-        let input_file = input_file(day);
-        let input = self
-            .load_input(input_file)
-            .expect("unable to open input file");
+    // rejects parameter values that the parts can't work with, before anything is solved
+    fn check_params(&self, _params: &Params) -> Result<(), Error> {
+        Ok(())
+    }
 
-        let start = Instant::now();
-        let s1 = self.solve_first(&input);
-        let time = start.elapsed();
-        println!("Solution 1: {:<20} ({}ms)", s1, time.as_secs_f64() * 1000.0);
+    // rejects an input that the parts can't solve with these parameters
+    fn check_input(&self, _input: &Self::Input, _params: &Params) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn solve_first_with(&self, input: &Self::Input, _params: &Params) -> Self::Output1 {
+        self.solve_first(input)
+    }
 
+    fn solve_second_with(&self, input: &Self::Input, _params: &Params) -> Self::Output2 {
+        self.solve_second(input)
+    }
+
+    // used when both parts are requested, so that work common to both parts is done once;
+    // implementations mark the end of each phase on the stopwatch
+    fn solve_both(
+        &self,
+        input: &Self::Input,
+        params: &Params,
+        sw: &mut Stopwatch,
+    ) -> (Self::Output1, Self::Output2) {
+        let s1 = self.solve_first_with(input, params);
+        sw.lap(Phase::First);
+        let s2 = self.solve_second_with(input, params);
+        sw.lap(Phase::Second);
+        (s1, s2)
+    }
+
+    // slow but obviously correct versions of the parts, that the real ones are checked
+    // against by `difftest`
+    fn reference_first(&self, _input: &Self::Input, _params: &Params) -> Option<Self::Output1> {
+        None
+    }
+
+    fn reference_second(&self, _input: &Self::Input, _params: &Params) -> Option<Self::Output2> {
+        None
+    }
+
+    // a random but valid puzzle input, roughly `size` lines or items long
+    fn generate(&self, _rng: &mut Rng, _size: usize) -> Option<String> {
+        None
+    }
+
+    fn read_input<R: Read>(&self, mut r: R) -> io::Result<Vec<u8>> {
+        let mut bytes = vec![];
+        r.read_to_end(&mut bytes)?;
+        Ok(if Self::RAW_INPUT {
+            bytes
+        } else {
+            normalize_input(&bytes)
+        })
+    }
+
+    fn load_input<P: AsRef<Path>>(&self, p: P) -> Result<Self::Input, Error> {
+        let bytes = self.read_input(File::open(p)?)?;
+        self.parse_input(bytes.as_slice())
+    }
+
+    fn solve(&self, day: u32, params: &Params) -> Result<(), Error> {
+        let file = input_file(day);
+        let bytes = File::open(&file)
+            .and_then(|f| self.read_input(f))
+            .map_err(|e| anyhow!("{file}: {e}"))?;
+        self.solve_timed(bytes.as_slice(), Parts::Both, params)
+            .map_err(|e| anyhow!("{file}: {e}"))?
+            .print();
+        Ok(())
+    }
+
+    fn solve_timed<R: Read>(&self, r: R, parts: Parts, params: &Params) -> Result<Report, Error> {
         let start = Instant::now();
-        let s2 = self.solve_second(&input);
-        let time = start.elapsed();
-        println!("Solution 2: {:<20} ({}ms)", s2, time.as_secs_f64() * 1000.0);
+        let input = self.parse_input(r)?;
+        trace::record("parse_input", start, Instant::now());
+        let parse_time = start.elapsed();
+        self.check_input(&input, params)?;
+
+        Ok(Report {
+            parse_time,
+            ..self.solve_parsed(&input, parts, params)
+        })
+    }
+
+    fn solve_parsed(&self, input: &Self::Input, parts: Parts, params: &Params) -> Report {
+        let mut answers = vec![];
+        let mut shared_time = None;
+
+        match parts {
+            Parts::Both => {
+                let _span = trace::span("solve_both");
+                let mut sw = Stopwatch::start();
+                let (s1, s2) = self.solve_both(input, params, &mut sw);
+                shared_time = sw.total(Phase::Shared);
+                answers.push(Answer::new(
+                    1,
+                    s1,
+                    sw.total(Phase::First).unwrap_or_default(),
+                ));
+                answers.push(Answer::new(
+                    2,
+                    s2,
+                    sw.total(Phase::Second).unwrap_or_default(),
+                ));
+            }
+            Parts::First => {
+                let start = Instant::now();
+                let s1 = self.solve_first_with(input, params);
+                trace::record(Phase::First.name(), start, Instant::now());
+                let time = start.elapsed();
+                answers.push(Answer::new(1, s1, time));
+            }
+            Parts::Second => {
+                let start = Instant::now();
+                let s2 = self.solve_second_with(input, params);
+                trace::record(Phase::Second.name(), start, Instant::now());
+                let time = start.elapsed();
+                answers.push(Answer::new(2, s2, time));
+            }
+        }
+
+        Report {
+            parse_time: Duration::ZERO,
+            shared_time,
+            answers,
+        }
+    }
+
+    // solves the examples then the real input, printing one `source\tpart\tanswer` line
+    // per answer with newlines escaped, which is what watch mode reads
+    fn check(&self, day: u32, params: &Params) {
+        let sources = example_files(day)
+            .into_iter()
+            .chain(once(PathBuf::from(input_file(day))));
+
+        for source in sources.filter(|s| s.exists()) {
+            let input = self
+                .load_input(&source)
+                .and_then(|input| self.check_input(&input, params).map(|_| input));
+            let input = match input {
+                Ok(input) => input,
+                Err(e) => {
+                    eprintln!("{}: {e}", source.display());
+                    continue;
+                }
+            };
+            let (s1, s2) = self.solve_both(&input, params, &mut Stopwatch::start());
+            let (s1, s2) = (s1.to_string(), s2.to_string());
+
+            let source = source.display();
+            println!("{source}\t1\t{}", escape_answer(&s1));
+            println!("{source}\t2\t{}", escape_answer(&s2));
+        }
+    }
+}
+
+fn escape_answer(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Params(BTreeMap<String, String>);
+
+impl Params {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, key: &str, value: impl Display) -> Self {
+        self.0.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    // for the parts, once the solver has checked the parameters
+    pub fn get<T: FromStr>(&self, key: &str) -> T {
+        self.try_get(key).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    pub fn try_get<T: FromStr>(&self, key: &str) -> Result<T, Error> {
+        let value = self
+            .0
+            .get(key)
+            .ok_or_else(|| anyhow!("missing parameter {key}"))?;
+        value
+            .parse()
+            .map_err(|_| anyhow!("invalid value for parameter {key}: {value}"))
+    }
+
+    pub fn at_least<T: FromStr + PartialOrd + Display>(
+        &self,
+        key: &str,
+        min: T,
+    ) -> Result<T, Error> {
+        let value = self.try_get(key)?;
+        if value < min {
+            return Err(anyhow!(
+                "parameter {key} must be at least {min}, got {value}"
+            ));
+        }
+        Ok(value)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    // parses a `key=value` override
+    pub fn set_from_str(&mut self, s: &str) -> Result<(), Error> {
+        let (key, value) = s
+            .split_once('=')
+            .ok_or_else(|| anyhow!("{s}: expected key=value"))?;
+        self.0
+            .insert(key.trim().to_string(), value.trim().to_string());
+        Ok(())
+    }
+
+    // applies overrides on top of these defaults, rejecting unknown keys
+    pub fn merge(&self, overrides: &Params) -> Result<Params, Error> {
+        let mut merged = self.clone();
+        for (key, value) in &overrides.0 {
+            match merged.0.get_mut(key) {
+                Some(v) => *v = value.clone(),
+                None if self.0.is_empty() => {
+                    Err(anyhow!("unknown parameter {key}, there are no parameters"))?
+                }
+                None => Err(anyhow!(
+                    "unknown parameter {key}, expected one of: {}",
+                    self.0.keys().cloned().collect::<Vec<_>>().join(", ")
+                ))?,
+            }
+        }
+        Ok(merged)
+    }
+}
+
+impl Display for Params {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (key, value) in &self.0 {
+            writeln!(f, "{key}={value}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Parts {
+    First,
+    Second,
+    Both,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Phase {
+    Shared,
+    First,
+    Second,
+}
+
+impl Phase {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Phase::Shared => "shared",
+            Phase::First => "solve_first",
+            Phase::Second => "solve_second",
+        }
+    }
+}
+
+pub struct Stopwatch {
+    last: Instant,
+    laps: Vec<(Phase, Duration)>,
+}
+
+impl Stopwatch {
+    pub fn start() -> Self {
+        Self {
+            last: Instant::now(),
+            laps: vec![],
+        }
+    }
+
+    // attributes the time elapsed since the previous lap to the given phase
+    pub fn lap(&mut self, phase: Phase) {
+        let now = Instant::now();
+        trace::record(phase.name(), self.last, now);
+        self.laps.push((phase, now - self.last));
+        self.last = now;
+    }
+
+    pub fn total(&self, phase: Phase) -> Option<Duration> {
+        self.laps
+            .iter()
+            .filter(|(p, _)| *p == phase)
+            .map(|(_, d)| *d)
+            .reduce(|a, b| a + b)
+    }
+}
+
+#[derive(Debug)]
+pub struct Answer {
+    pub part: u8,
+    pub value: String,
+    pub time: Duration,
+}
+
+impl Answer {
+    fn new(part: u8, value: impl Display, time: Duration) -> Self {
+        Self {
+            part,
+            value: value.to_string(),
+            time,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Report {
+    pub parse_time: Duration,
+    pub shared_time: Option<Duration>,
+    pub answers: Vec<Answer>,
+}
+
+impl Report {
+    pub fn print(&self) {
+        if let Some(time) = self.shared_time {
+            println!("Shared:     {:<20} ({}ms)", "", time.as_secs_f64() * 1000.0);
+        }
+        for a in &self.answers {
+            println!(
+                "Solution {}: {:<20} ({}ms)",
+                a.part,
+                a.value,
+                a.time.as_secs_f64() * 1000.0
+            );
+        }
+    }
+}
+
+// object-safe view of a `Solver`, so that days can be looked up at runtime
+pub trait Runner {
+    fn default_params(&self) -> Params;
+    fn params(&self, overrides: &Params) -> Result<Params, Error>;
+    fn run(&self, day: u32, params: &Params) -> Result<(), Error>;
+    fn check(&self, day: u32, params: &Params);
+    fn solve_bytes(&self, input: &[u8], parts: Parts, params: &Params) -> Result<Report, Error>;
+    fn repl(&self, day: u32, params: Params) -> Result<(), Error>;
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String>;
+    fn difftest(&self, day: u32, options: &DiffOptions, params: &Params) -> Result<(), Error>;
+}
+
+impl<S: Solver> Runner for S {
+    fn default_params(&self) -> Params {
+        Solver::default_params(self)
+    }
+
+    fn params(&self, overrides: &Params) -> Result<Params, Error> {
+        let params = Solver::default_params(self).merge(overrides)?;
+        self.check_params(&params)?;
+        Ok(params)
+    }
+
+    fn run(&self, day: u32, params: &Params) -> Result<(), Error> {
+        Solver::solve(self, day, params)
+    }
+
+    fn check(&self, day: u32, params: &Params) {
+        Solver::check(self, day, params)
+    }
+
+    fn solve_bytes(&self, input: &[u8], parts: Parts, params: &Params) -> Result<Report, Error> {
+        // reading from a slice can't fail
+        let bytes = self.read_input(input).unwrap_or_default();
+        self.solve_timed(bytes.as_slice(), parts, params)
+    }
+
+    fn repl(&self, day: u32, params: Params) -> Result<(), Error> {
+        repl::repl(self, day, params)
+    }
+
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Solver::generate(self, rng, size)
+    }
+
+    fn difftest(&self, day: u32, options: &DiffOptions, params: &Params) -> Result<(), Error> {
+        difftest::difftest(self, day, options, params)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseError {
+    pub column: usize,
+    pub expected: String,
+    pub found: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "column {}: expected {}, found {}",
+            self.column, self.expected, self.found
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// one of the parsers tried in turn by `Cursor::alt`
+pub type Alternative<'a, T> = dyn Fn(&mut Cursor<'a>) -> Result<T, ParseError>;
+
+// Reads typed tokens off a line, left to right. Each method either consumes what
+// it expects or leaves the cursor untouched and reports what it found instead:
+//
+//     let mut c = Cursor::new("move 3 from 1 to 2");
+//     c.literal("move ")?;
+//     let n: usize = c.number()?;
+#[derive(Clone, Debug)]
+pub struct Cursor<'a> {
+    line: &'a str,
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    pub fn new(line: &'a str) -> Self {
+        Self { line, pos: 0 }
+    }
+
+    // parses a whole line, failing if anything is left over
+    pub fn parse<T, F>(line: &'a str, f: F) -> Result<T, ParseError>
+    where
+        F: FnOnce(&mut Self) -> Result<T, ParseError>,
+    {
+        let mut c = Self::new(line);
+        let value = f(&mut c)?;
+        c.end()?;
+        Ok(value)
+    }
+
+    pub fn rest(&self) -> &'a str {
+        &self.line[self.pos..]
+    }
+
+    pub fn error(&self, expected: impl Display) -> ParseError {
+        let rest = self.rest();
+        let found = match rest.char_indices().nth(12) {
+            _ if rest.is_empty() => "end of line".to_string(),
+            Some((i, _)) => format!("\"{}...\"", &rest[..i]),
+            None => format!("\"{rest}\""),
+        };
+
+        ParseError {
+            column: self.line[..self.pos].chars().count() + 1,
+            expected: expected.to_string(),
+            found,
+        }
+    }
+
+    pub fn literal(&mut self, lit: &str) -> Result<(), ParseError> {
+        if self.rest().starts_with(lit) {
+            self.pos += lit.len();
+            Ok(())
+        } else {
+            Err(self.error(format!("\"{lit}\"")))
+        }
+    }
+
+    // a run of characters up to the next whitespace
+    pub fn word(&mut self) -> Result<&'a str, ParseError> {
+        let rest = self.rest();
+        let len = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.error("a word"));
+        }
+        self.pos += len;
+        Ok(&rest[..len])
+    }
+
+    pub fn number<T: FromStr>(&mut self) -> Result<T, ParseError> {
+        let rest = self.rest();
+        let sign = usize::from(rest.starts_with(['-', '+']));
+        let digits = rest[sign..]
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len() - sign);
+        let expected = || format!("a {}", std::any::type_name::<T>());
+
+        if digits == 0 {
+            return Err(self.error(expected()));
+        }
+        let value = rest[..sign + digits]
+            .parse()
+            .map_err(|_| self.error(expected()))?;
+        self.pos += sign + digits;
+        Ok(value)
+    }
+
+    // matches the first of the given tokens and returns its value
+    pub fn one_of<T: Clone>(&mut self, options: &[(&str, T)]) -> Result<T, ParseError> {
+        for (token, value) in options {
+            if self.literal(token).is_ok() {
+                return Ok(value.clone());
+            }
+        }
+
+        let tokens = options
+            .iter()
+            .map(|(t, _)| format!("\"{t}\""))
+            .collect::<Vec<_>>();
+        Err(self.error(format!("one of {}", tokens.join(", "))))
+    }
+
+    // one or more items separated by `sep`
+    pub fn list<T, F>(&mut self, sep: &str, mut item: F) -> Result<Vec<T>, ParseError>
+    where
+        F: FnMut(&mut Self) -> Result<T, ParseError>,
+    {
+        let mut items = vec![item(self)?];
+        while self.literal(sep).is_ok() {
+            items.push(item(self)?);
+        }
+        Ok(items)
+    }
+
+    // tries each alternative from the same position, keeping the first that succeeds,
+    // or the error of the one that went the furthest
+    pub fn alt<T>(&mut self, alternatives: &[&Alternative<'a, T>]) -> Result<T, ParseError> {
+        let mut best: Option<ParseError> = None;
+
+        for f in alternatives {
+            let mut c = self.clone();
+            match f(&mut c) {
+                Ok(value) => {
+                    *self = c;
+                    return Ok(value);
+                }
+                Err(e) if best.as_ref().is_some_and(|b| b.column >= e.column) => {}
+                Err(e) => best = Some(e),
+            }
+        }
+
+        Err(best.unwrap_or_else(|| self.error("something")))
+    }
+
+    pub fn end(&self) -> Result<(), ParseError> {
+        if self.rest().is_empty() {
+            Ok(())
+        } else {
+            Err(self.error("end of line"))
+        }
     }
 }
 
@@ -47,6 +643,14 @@ pub trait ReadExt<T> {
     fn split_commas(self) -> Vec<T>;
     fn split_lines(self) -> Vec<T>;
     fn split_groups(self) -> Vec<T>;
+    fn try_split_lines(self) -> Result<Vec<T>, Error>
+    where
+        T: FromStr,
+        T::Err: Display;
+    fn try_split_groups(self) -> Result<Vec<T>, Error>
+    where
+        T: FromStr,
+        T::Err: Display;
 }
 
 impl<R, T> ReadExt<T> for R
@@ -70,7 +674,7 @@ where
     fn split_lines(self) -> Vec<T> {
         BufReader::new(self)
             .lines()
-            .flatten()
+            .map_while(Result::ok)
             .flat_map(|l| l.parse())
             .collect()
     }
@@ -78,10 +682,141 @@ where
     fn split_groups(self) -> Vec<T> {
         BufReader::new(self)
             .lines()
-            .flatten()
+            .map_while(Result::ok)
             .collect::<Vec<_>>()
             .split(|l| l.is_empty())
             .flat_map(|e| e.join("\n").parse())
             .collect::<Vec<T>>()
     }
+
+    // like split_lines, but stops at the first line that doesn't parse
+    fn try_split_lines(self) -> Result<Vec<T>, Error>
+    where
+        T::Err: Display,
+    {
+        BufReader::new(self)
+            .lines()
+            .enumerate()
+            .map(|(i, l)| {
+                l.map_err(Error::from)?
+                    .parse()
+                    .map_err(|e| anyhow!("line {}: {e}", i + 1))
+            })
+            .collect()
+    }
+
+    fn try_split_groups(self) -> Result<Vec<T>, Error>
+    where
+        T::Err: Display,
+    {
+        let lines = BufReader::new(self)
+            .lines()
+            .collect::<Result<Vec<_>, _>>()?;
+
+        lines
+            .split(|l| l.is_empty())
+            .enumerate()
+            .map(|(i, e)| {
+                e.join("\n")
+                    .parse()
+                    .map_err(|e| anyhow!("group {}: {e}", i + 1))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solutions::solver;
+
+    #[test]
+    fn test_params() {
+        let defaults = Params::new().with("size", 4);
+        let mut overrides = Params::new();
+        overrides.set_from_str("size = 2").unwrap();
+        assert_eq!(defaults.merge(&overrides).unwrap().get::<usize>("size"), 2);
+
+        overrides.set_from_str("other=1").unwrap();
+        assert!(defaults.merge(&overrides).is_err());
+
+        let params = Params::new().with("size", "abc");
+        assert!(params.try_get::<usize>("size").is_err());
+        assert!(params.try_get::<usize>("missing").is_err());
+        assert!(Params::new()
+            .with("size", 0)
+            .at_least("size", 1usize)
+            .is_err());
+    }
+
+    #[test]
+    fn test_invalid_params() {
+        let invalid = [
+            (6, "packet_marker_size=0"),
+            (7, "disk_size=-1"),
+            (9, "knots1=1"),
+            (9, "knots2=abc"),
+            (10, "width=0"),
+            (11, "rounds2=0"),
+            (11, "relief=0"),
+        ];
+
+        for (day, p) in invalid {
+            let s = solver(day).unwrap();
+            let mut overrides = Params::new();
+            overrides.set_from_str(p).unwrap();
+            assert!(s.params(&overrides).is_err(), "day {day}: {p}");
+            assert!(s.params(&Params::new()).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_cursor() {
+        let (n, from, to) = Cursor::parse("move 3 from 1 to 2", |c| {
+            c.literal("move ")?;
+            let n: usize = c.number()?;
+            c.literal(" from ")?;
+            let from: usize = c.number()?;
+            c.literal(" to ")?;
+            Ok((n, from, c.number::<usize>()?))
+        })
+        .unwrap();
+        assert_eq!((n, from, to), (3, 1, 2));
+
+        let items = Cursor::parse("79, 98", |c| c.list(", ", |c| c.number::<u64>()));
+        assert_eq!(items, Ok(vec![79, 98]));
+
+        let err = Cursor::parse("addx 3x", |c| {
+            c.literal("addx ")?;
+            c.number::<i64>()
+        });
+        assert_eq!(
+            err.unwrap_err().to_string(),
+            "column 7: expected end of line, found \"x\""
+        );
+
+        let err = Cursor::parse("1-300", |c| {
+            let a: u8 = c.number()?;
+            c.literal("-")?;
+            Ok((a, c.number::<u8>()?))
+        });
+        assert_eq!(
+            err.unwrap_err().to_string(),
+            "column 3: expected a u8, found \"300\""
+        );
+
+        let op = |c: &mut Cursor| c.alt(&[&|c| c.literal("old").map(|_| 0), &|c| c.number()]);
+        assert_eq!(Cursor::parse("old", op), Ok(0));
+        assert_eq!(Cursor::parse("12", op), Ok(12));
+    }
+
+    #[test]
+    fn test_normalize_input() {
+        assert_eq!(
+            normalize_input(b"\xEF\xBB\xBF1000\r\n2000 \r\n\r\n3000\r\n\r\n\r\n"),
+            b"1000\n2000\n\n3000\n"
+        );
+        assert_eq!(normalize_input(b"a\rb\t"), b"a\nb\n");
+        assert_eq!(normalize_input(b" \n\n"), b"");
+    }
 }
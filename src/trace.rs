@@ -0,0 +1,92 @@
+use std::cell::RefCell;
+use std::fmt::Write as FmtWrite;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Instant;
+
+struct Event {
+    name: &'static str,
+    start: Instant,
+    end: Instant,
+}
+
+struct Recorder {
+    epoch: Instant,
+    events: Vec<Event>,
+}
+
+thread_local! {
+    static RECORDER: RefCell<Option<Recorder>> = const { RefCell::new(None) };
+}
+
+pub fn start() {
+    RECORDER.with(|cell| {
+        *cell.borrow_mut() = Some(Recorder {
+            epoch: Instant::now(),
+            events: vec![],
+        })
+    });
+}
+
+pub fn enabled() -> bool {
+    RECORDER.with(|cell| cell.borrow().is_some())
+}
+
+pub fn record(name: &'static str, start: Instant, end: Instant) {
+    RECORDER.with(|cell| {
+        if let Some(r) = cell.borrow_mut().as_mut() {
+            r.events.push(Event { name, start, end });
+        }
+    });
+}
+
+// times the enclosing scope: `let _span = trace::span("name");`
+pub fn span(name: &'static str) -> Span {
+    Span {
+        name,
+        start: enabled().then(Instant::now),
+    }
+}
+
+pub struct Span {
+    name: &'static str,
+    start: Option<Instant>,
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        if let Some(start) = self.start {
+            record(self.name, start, Instant::now());
+        }
+    }
+}
+
+// stops recording and writes the events in the Chrome trace event format,
+// which chrome://tracing or Perfetto can open
+pub fn finish<P: AsRef<Path>>(p: P) -> io::Result<()> {
+    let recorder = match RECORDER.with(|cell| cell.borrow_mut().take()) {
+        Some(r) => r,
+        None => return Ok(()),
+    };
+
+    let micros = |t: Instant| t.duration_since(recorder.epoch).as_secs_f64() * 1e6;
+
+    let mut s = String::from("{\"traceEvents\":[\n");
+    for (i, e) in recorder.events.iter().enumerate() {
+        if i > 0 {
+            s.push_str(",\n");
+        }
+        // span names are identifiers from the code, no escaping needed
+        let _ = write!(
+            s,
+            "{{\"name\":\"{}\",\"cat\":\"solver\",\"ph\":\"X\",\"ts\":{:.3},\"dur\":{:.3},\"pid\":1,\"tid\":1}}",
+            e.name,
+            micros(e.start),
+            micros(e.end) - micros(e.start)
+        );
+    }
+    s.push_str("\n],\"displayTimeUnit\":\"ms\"}\n");
+
+    fs::write(p, s)
+}
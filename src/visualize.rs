@@ -0,0 +1,115 @@
+use crate::grid::Grid;
+use std::cell::RefCell;
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::io::Write;
+use std::path::PathBuf;
+use std::thread::sleep;
+use std::time::Duration;
+
+pub const DEFAULT_FPS: f64 = 10.0;
+
+pub trait Visualizer {
+    fn frame(&mut self, frame: &str);
+
+    fn finish(&mut self) {}
+}
+
+thread_local! {
+    static VISUALIZER: RefCell<Option<Box<dyn Visualizer>>> = const { RefCell::new(None) };
+}
+
+pub fn install(v: Box<dyn Visualizer>) {
+    VISUALIZER.with(|cell| *cell.borrow_mut() = Some(v));
+}
+
+pub fn finish() {
+    if let Some(mut v) = VISUALIZER.with(|cell| cell.borrow_mut().take()) {
+        v.finish();
+    }
+}
+
+pub fn enabled() -> bool {
+    VISUALIZER.with(|cell| cell.borrow().is_some())
+}
+
+// the closure is only called when a visualizer is installed, so solvers can emit
+// frames from hot loops without paying for rendering
+pub fn emit<F>(render: F)
+where
+    F: FnOnce() -> String,
+{
+    if !enabled() {
+        return;
+    }
+
+    let frame = render();
+    VISUALIZER.with(|cell| {
+        if let Some(v) = cell.borrow_mut().as_mut() {
+            v.frame(&frame);
+        }
+    });
+}
+
+pub fn emit_grid<T: Display>(grid: &Grid<T>) {
+    emit(|| grid.to_string());
+}
+
+pub struct TerminalPlayer {
+    delay: Duration,
+    count: usize,
+}
+
+impl TerminalPlayer {
+    pub fn new(fps: f64) -> Self {
+        Self {
+            delay: Duration::from_secs_f64(1.0 / fps.max(0.001)),
+            count: 0,
+        }
+    }
+}
+
+impl Visualizer for TerminalPlayer {
+    fn frame(&mut self, frame: &str) {
+        self.count += 1;
+
+        let mut out = io::stdout().lock();
+        // clear screen and move the cursor home
+        let _ = write!(out, "\x1b[2J\x1b[H{frame}");
+        if !frame.ends_with('\n') {
+            let _ = writeln!(out);
+        }
+        let _ = writeln!(out, "frame {}", self.count);
+        let _ = out.flush();
+
+        sleep(self.delay);
+    }
+}
+
+pub struct FrameDump {
+    dir: PathBuf,
+    count: usize,
+}
+
+impl FrameDump {
+    pub fn new(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir, count: 0 })
+    }
+}
+
+impl Visualizer for FrameDump {
+    fn frame(&mut self, frame: &str) {
+        let path = self.dir.join(format!("frame{:06}.txt", self.count));
+        if let Err(e) = fs::write(&path, frame) {
+            eprintln!("unable to write {}: {e}", path.display());
+        }
+        self.count += 1;
+    }
+
+    fn finish(&mut self) {
+        println!("{} frames written to {}", self.count, self.dir.display());
+    }
+}
@@ -0,0 +1,140 @@
+use crate::snapshot::line_diff;
+use crate::solver::{example_files, examples_dir, input_file, Params};
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread::sleep;
+use std::time::{Duration, SystemTime};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+struct Answer {
+    source: String,
+    part: String,
+    value: String,
+}
+
+pub fn watch(day: u32, overrides: &Params) -> io::Result<()> {
+    // resolved once, as rebuilding replaces the file this process was started from
+    let exe = env::current_exe()?;
+    let mut last_change = None;
+    let mut previous = vec![];
+
+    println!("Watching day {day}, press Ctrl-C to stop");
+
+    loop {
+        let change = last_modified(&watched_paths(day));
+        if change != last_change {
+            last_change = change;
+
+            if rebuild() {
+                let answers = check(&exe, day, overrides)?;
+                print_answers(&answers, &previous);
+                previous = answers;
+            } else {
+                println!("Build failed, waiting for changes...");
+            }
+        }
+
+        sleep(POLL_INTERVAL);
+    }
+}
+
+fn watched_paths(day: u32) -> Vec<PathBuf> {
+    let mut paths = vec![
+        PathBuf::from(format!("src/solutions/day{:02}.rs", day)),
+        PathBuf::from(input_file(day)),
+        // the directory itself changes when examples are added or removed
+        PathBuf::from(examples_dir(day)),
+    ];
+    paths.extend(example_files(day));
+    paths
+}
+
+fn last_modified(paths: &[PathBuf]) -> Option<SystemTime> {
+    paths
+        .iter()
+        .filter_map(|p| fs::metadata(p).and_then(|m| m.modified()).ok())
+        .max()
+}
+
+fn rebuild() -> bool {
+    let cargo = env::var("CARGO").unwrap_or_else(|_| String::from("cargo"));
+    let mut cmd = Command::new(cargo);
+    cmd.args(["build", "--quiet"]);
+    if !cfg!(debug_assertions) {
+        cmd.arg("--release");
+    }
+
+    cmd.status().map(|s| s.success()).unwrap_or(false)
+}
+
+// runs the freshly built binary, since this process still holds the old code
+fn check(exe: &Path, day: u32, overrides: &Params) -> io::Result<Vec<Answer>> {
+    let mut cmd = Command::new(exe);
+    cmd.args(["check", &day.to_string()]);
+    for (key, value) in overrides.iter() {
+        cmd.arg("--param").arg(format!("{key}={value}"));
+    }
+
+    let output = cmd.stderr(Stdio::inherit()).output()?;
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|l| {
+            let mut fields = l.splitn(3, '\t');
+            Some(Answer {
+                source: fields.next()?.to_string(),
+                part: fields.next()?.to_string(),
+                value: unescape_answer(fields.next()?),
+            })
+        })
+        .collect())
+}
+
+fn unescape_answer(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') => out.push('\n'),
+                Some(c) => out.push(c),
+                None => out.push('\\'),
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn print_answers(answers: &[Answer], previous: &[Answer]) {
+    println!();
+    if answers.is_empty() {
+        println!("No answers, is the day solved and its input present?");
+    }
+
+    for a in answers {
+        let prev = previous
+            .iter()
+            .find(|p| p.source == a.source && p.part == a.part)
+            .map(|p| p.value.as_str());
+        let value = a.value.trim_matches('\n');
+
+        print!("[{}] part {}: ", a.source, a.part);
+        match prev.map(|p| p.trim_matches('\n')) {
+            Some(p) if p != value && !value.contains('\n') && !p.contains('\n') => {
+                println!("{value} (was {p})")
+            }
+            Some(p) if p != value => {
+                println!("changed");
+                print!("{}", line_diff(p, value));
+            }
+            _ if value.contains('\n') => println!("\n{value}"),
+            _ => println!("{value}"),
+        }
+    }
+}